@@ -74,19 +74,19 @@ impl<C: CharBool> CharBool for CharNot<C> {
 }
 
 pub fn is_alpha(c: char) -> bool {
-    (c >= 'A' && c <= 'Z') || (c >= 'a' && c <= 'z')
+    c.is_ascii_alphabetic()
 }
 char_bool!(Alpha, is_alpha);
 
 pub fn is_num(c: char) -> bool {
-    c >= '0' && c <= '9'
+    c.is_ascii_digit()
 }
 char_bool!(NumDigit, is_num);
 
 char_bool!(Any, |_| true);
 
 pub fn is_hex(c: char) -> bool {
-    is_num(c) || (c >= 'a' && c <= 'f') || (c >= 'A' && c <= 'F')
+    c.is_ascii_hexdigit()
 }
 char_bool!(HexDigit, is_hex);
 
@@ -230,7 +230,7 @@ impl<A: CharBool, E: CharBool> CharBool for CharsExcept<A, E> {
 }
 
 pub fn do_one_char<'a, CB: CharBool>(i: &PIter<'a>, cb: &CB) -> ParseRes<'a, char> {
-    let mut i2 = i.clone();
+    let mut i2 = *i;
     let ic = i2.next().ok_or(i2.err(cb.expected()))?;
     if cb.char_bool(ic) {
         Ok((i2, ic, None))
@@ -260,10 +260,10 @@ pub fn do_chars<'a, CB: CharBool>(
     min: usize,
     exact: bool,
 ) -> ParseRes<'a, ()> {
-    let mut it = i.clone();
+    let mut it = *i;
     let mut done = 0;
     loop {
-        let it2 = it.clone();
+        let it2 = it;
         match it.next() {
             Some(c) if cb.char_bool(c) => {
                 //println!("do_chars CHAR = {}", c);
@@ -367,7 +367,7 @@ pub struct CharUntil<A: CharBool, E> {
 impl<'a, A: CharBool, E: Parser<'a>> Parser<'a> for CharUntil<A, E> {
     type Out = (&'a str, E::Out);
     fn parse(&self, i_start: &PIter<'a>) -> ParseRes<'a, Self::Out> {
-        let mut it = i_start.clone();
+        let mut it = *i_start;
         let mut a_stop = it.index();
         loop {
             match self.end.parse(&it) {
@@ -382,10 +382,10 @@ impl<'a, A: CharBool, E: Parser<'a>> Parser<'a> for CharUntil<A, E> {
 }
 
 #[cfg(test)]
-pub mod test {
+mod test {
     use super::*;
     #[test]
     pub fn test_alpha_works_as_struct() {
-        assert_eq!(Alpha.char_bool('a'), true)
+        assert!(Alpha.char_bool('a'))
     }
 }
@@ -1,4 +1,5 @@
 use crate::err::*;
+use crate::hooks::trace_rule;
 use crate::iter::*;
 use crate::parser::*;
 use std::fmt::Debug;
@@ -14,7 +15,8 @@ where
     fn parse(&self, i: &PIter<'a>) -> ParseRes<'a, Self::Out> {
         match self.0.parse(i) {
             Ok((ir, v, ex)) => Ok((ir, Some(v), ex)),
-            Err(e) => Ok((i.clone(), None, Some(e))),
+            Err(e) if e.is_cut => Err(e),
+            Err(e) => Ok((*i, None, Some(e))),
         }
     }
 }
@@ -50,7 +52,8 @@ impl<'a, P: Parser<'a>> Parser<'a> for Exists<P> {
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, bool> {
         match self.p.parse(it) {
             Ok((nit, _, e)) => Ok((nit, true, e)),
-            Err(e) => Ok((it.clone(), false, Some(e))),
+            Err(e) if e.is_cut => Err(e),
+            Err(e) => Ok((*it, false, Some(e))),
         }
     }
 }
@@ -91,7 +94,7 @@ impl<'a, P: Parser<'a, Out = V>, V: Debug> Parser<'a> for FailOn<P> {
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, ()> {
         match self.0.parse(it) {
             Ok(_) => it.err_r(Expected::Str("Failon Succeeded")),
-            Err(_) => Ok((it.clone(), (), None)),
+            Err(_) => Ok((*it, (), None)),
         }
     }
 }
@@ -108,6 +111,8 @@ pub struct PDebugger<P> {
     s: &'static str,
 }
 
+/// Records a node labelled s while `traced` is running, and counts it while `profiled` is.
+/// Without the `trace` feature it only runs p.
 pub fn debug<'a, P: Parser<'a>>(p: P, s: &'static str) -> PDebugger<P> {
     PDebugger { p, s }
 }
@@ -115,18 +120,34 @@ pub fn debug<'a, P: Parser<'a>>(p: P, s: &'static str) -> PDebugger<P> {
 impl<'a, P: Parser<'a>> Parser<'a> for PDebugger<P> {
     type Out = P::Out;
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
-        println!("DebuggerPre - {}", self.s);
-        let r = self.p.parse(it);
-        match &r {
-            Ok((nit, _, _)) => {
-                let s = match (it.index(), nit.index()) {
-                    (Some(st), Some(f)) => &it.as_str()[..f - st],
-                    _ => it.as_str(),
-                };
-                println!("Success - {}, \"{}\"", self.s, s);
-            }
-            Err(_) => println!("Fail - {}", self.s),
-        };
-        r
+        trace_rule(self.s, it, || self.p.parse(it))
+    }
+}
+
+#[derive(Clone)]
+pub struct Context<P> {
+    p: P,
+    s: &'static str,
+}
+
+/// Adds a label to the context of any error from p, so messages say which rule failed.
+/// Rules made by [parser!] add their name the same way.
+///
+/// ```rust
+/// use bogobble::*;
+/// let cond = middle(ws_('('), ws_(common::Ident), ws_(')')).context("condition");
+/// let if_ex = (keyword("if"), cond, ws_('{')).context("if-expression");
+/// let e = if_ex.parse_s("if (a b) {").unwrap_err();
+/// assert_eq!(e.context(), ["if-expression", "condition"]);
+/// assert!(e.to_string().starts_with("while parsing if-expression > condition: Expected"));
+/// ```
+pub fn context<'a, P: Parser<'a>>(p: P, s: &'static str) -> Context<P> {
+    Context { p, s }
+}
+
+impl<'a, P: Parser<'a>> Parser<'a> for Context<P> {
+    type Out = P::Out;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
+        self.p.parse(it).map_err(|e| e.in_context(self.s))
     }
 }
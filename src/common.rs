@@ -61,9 +61,9 @@ parser! { "a usize"
 pub fn common_uint<'a>(it: &PIter<'a>) -> ParseRes<'a, usize> {
     let mut added = false;
     let mut res: usize = 0;
-    let mut it = it.clone();
+    let mut it = *it;
     loop {
-        let it2 = it.clone();
+        let it2 = it;
         match it.next() {
             Some(v) if is_num(v) => {
                 added = true;
@@ -103,12 +103,12 @@ parser! { "the words 'true' or 'false'"
 fn dot_part<'a>(i: &PIter<'a>) -> ParseRes<'a, f64> {
     let mut res = 0.;
     let mut exp = 0.1;
-    let mut it = i.clone();
+    let mut it = *i;
     if it.next() != Some('.') {
         return i.err_rs("A Dot");
     }
     loop {
-        let it2 = it.clone();
+        let it2 = it;
         match it.next() {
             Some('_') => {}
             Some(v) if is_num(v) => {
@@ -150,7 +150,7 @@ parser! { "floating point numbers eg '134.4e6'"
 }
 
 #[cfg(test)]
-pub mod test {
+mod test {
     use super::*;
     #[test]
     pub fn test_parse_numbers() {
@@ -7,8 +7,8 @@ pub fn asv<'a, A: Parser<'a>, V: Clone>(a: A, v: V) -> Asv<A, V> {
 }
 
 pub struct Asv<A, V: Clone> {
-    a: A,
-    v: V,
+    pub(crate) a: A,
+    pub(crate) v: V,
 }
 
 impl<'a, A: Parser<'a>, V: Clone> Parser<'a> for Asv<A, V> {
@@ -24,8 +24,8 @@ pub fn map<'a, A: Parser<'a>, F: Fn(A::Out) -> V, V>(a: A, f: F) -> Map<A, F> {
 
 #[derive(Clone)]
 pub struct Map<A, F> {
-    a: A,
-    f: F,
+    pub(crate) a: A,
+    pub(crate) f: F,
 }
 
 impl<'a, A: Parser<'a>, B, F: Fn(A::Out) -> B> Parser<'a> for Map<A, F> {
@@ -45,8 +45,8 @@ pub fn try_map<'a, A: Parser<'a>, F: Fn(A::Out) -> Result<V, Expected>, V>(
 
 #[derive(Clone)]
 pub struct TryMap<A, F> {
-    a: A,
-    f: F,
+    pub(crate) a: A,
+    pub(crate) f: F,
 }
 
 impl<'a, A: Parser<'a>, B, F: Fn(A::Out) -> Result<B, Expected>> Parser<'a> for TryMap<A, F> {
@@ -59,3 +59,147 @@ impl<'a, A: Parser<'a>, B, F: Fn(A::Out) -> Result<B, Expected>> Parser<'a> for
         }
     }
 }
+
+#[derive(Clone)]
+pub struct Verify<A, F, M> {
+    a: A,
+    f: F,
+    m: M,
+}
+
+/// Fails if f rejects the result of a, with the message from m. The error covers what a
+/// consumed, as that is the text being rejected.
+///
+/// ```rust
+/// use bogobble::*;
+/// let port = common::UInt.verify(|n| *n < 65536, |n| format!("a port below 65536, got {}", n));
+/// assert_eq!(port.parse_s("8080"), Ok(8080));
+/// let e = last("port ", port).parse_s("port 70000").unwrap_err();
+/// assert_eq!(e.exp, Expected::Msg("a port below 65536, got 70000".to_string()));
+/// assert_eq!(e.span("port 70000"), Some(Span::new(5, 10)));
+/// assert!(e.strung().to_string().contains("Expected 'a port below 65536, got 70000'"));
+/// ```
+pub fn verify<'a, A, F, M>(a: A, f: F, m: M) -> Verify<A, F, M>
+where
+    A: Parser<'a>,
+    F: Fn(&A::Out) -> bool,
+    M: Fn(&A::Out) -> String,
+{
+    Verify { a, f, m }
+}
+
+impl<'a, A, F, M> Parser<'a> for Verify<A, F, M>
+where
+    A: Parser<'a>,
+    F: Fn(&A::Out) -> bool,
+    M: Fn(&A::Out) -> String,
+{
+    type Out = A::Out;
+    fn parse(&self, i: &PIter<'a>) -> ParseRes<'a, A::Out> {
+        let (ri, v, ex) = self.a.parse(i)?;
+        match (self.f)(&v) {
+            true => Ok((ri, v, ex)),
+            false => Err(i.err(Expected::Msg((self.m)(&v))).with_end(ri.index())),
+        }
+    }
+}
+
+/// Builds the next parser from the result of the first.
+///
+/// ```rust
+/// use bogobble::*;
+/// // A heredoc, the closing line must match the tag
+/// let p = last("<<", string(Alpha.plus())).and_then(|tag| {
+///     last('\n', star_until(Any.one(), ('\n', matching(tag), eoi))).map(|(v, _)| v)
+/// });
+/// let v = p.parse_s("<<END\nhello\nEND?\nEND").unwrap();
+/// assert_eq!(v.into_iter().collect::<String>(), "hello\nEND?");
+///
+/// // A length prefixed field
+/// let p = first(common::UInt, ':').and_then(|n| string(Any.exact(n)));
+/// assert_eq!(p.parse_s("5:hello world"), Ok("hello".to_string()));
+/// ```
+pub fn and_then<'a, A, B, F>(a: A, f: F) -> AndThen<A, F>
+where
+    A: Parser<'a>,
+    B: Parser<'a>,
+    F: Fn(A::Out) -> B,
+{
+    AndThen { a, f }
+}
+
+#[derive(Clone)]
+pub struct AndThen<A, F> {
+    pub(crate) a: A,
+    pub(crate) f: F,
+}
+
+impl<'a, A: Parser<'a>, B: Parser<'a>, F: Fn(A::Out) -> B> Parser<'a> for AndThen<A, F> {
+    type Out = B::Out;
+    fn parse(&self, i: &PIter<'a>) -> ParseRes<'a, B::Out> {
+        let (ri, v, ex) = self.a.parse(i)?;
+        (self.f)(v).parse(&ri).join_err_op(ex)
+    }
+}
+
+/// Matches exactly some text captured earlier in the parse, returning it from the input
+///
+/// ```rust
+/// use bogobble::*;
+/// // Raw strings with any number of '#'
+/// let p = ('r', str_range('#'.istar()), '"').and_then(|(_, h, _)| {
+///     star_until(Any.one(), ('"', matching(h))).map(|(v, _)| v.into_iter().collect::<String>())
+/// });
+/// assert_eq!(p.parse_s(r###"r##"a "# b"##"###), Ok(r##"a "# b"##.to_string()));
+/// ```
+pub fn matching<S: AsRef<str>>(s: S) -> Matching<S> {
+    Matching { s }
+}
+
+#[derive(Clone)]
+pub struct Matching<S> {
+    s: S,
+}
+
+impl<'a, S: AsRef<str>> Parser<'a> for Matching<S> {
+    type Out = &'a str;
+    fn parse(&self, i: &PIter<'a>) -> ParseRes<'a, &'a str> {
+        let s = self.s.as_ref();
+        let mut ri = *i;
+        for c in s.chars() {
+            match ri.next() {
+                Some(ic) if ic == c => {}
+                _ => {
+                    let e = i.err(Expected::Msg(format!("the captured text {:?}", s)));
+                    return Err(e.cover_to(ri.index()));
+                }
+            }
+        }
+        Ok((ri, i.str_to(ri.index()), None))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_msg_errors_join() {
+        let unit = string(Alpha.plus()).verify(
+            |u| ["s", "ms"].contains(&u.as_str()),
+            |u| format!("a unit, not '{}'", u),
+        );
+        let p = or(unit, string(NumDigit.plus()).asv("digits".to_string()));
+        let e = p.parse_s("qz").unwrap_err();
+        assert_eq!(
+            e.exp,
+            Expected::OneOf(vec![
+                Expected::Msg("a unit, not 'qz'".to_string()),
+                Expected::CharIn("NumDigit")
+            ])
+        );
+        let e = ("<", matching("ab"), ">").parse_s("<ac>").unwrap_err();
+        assert_eq!(e.span("<ac>"), Some(Span::new(1, 3)));
+        assert_eq!(e.strung().exp.to_string(), "the captured text \"ab\"");
+    }
+}
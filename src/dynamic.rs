@@ -0,0 +1,136 @@
+//! Parsers as trait objects, for grammars put together at runtime.
+//!
+//! [Parser] is not object safe, so [DynParser] wraps it with a fixed output type.
+//! `Box`, `Rc` and `Arc` of a `dyn DynParser` are Parsers again, so they can be stored in
+//! collections and struct fields, and combined as normal.
+//!
+//! ```rust
+//! use bogobble::*;
+//! use std::collections::HashMap;
+//!
+//! // Commands loaded from user config
+//! let conf = [("go", 2), ("stop", 0), ("turn", 1)];
+//! let mut cmds: Vec<BoxParser<(&str, Vec<isize>)>> = Vec::new();
+//! for (name, n) in conf {
+//!     let p = (keyword(name), exact(ws_(common::Int), n));
+//!     cmds.push(p.boxed());
+//! }
+//! let cmd = or_list(cmds);
+//! assert_eq!(cmd.parse_s("turn 90"), Ok(("turn", vec![90])));
+//! assert_eq!(cmd.parse_s("go 3 4"), Ok(("go", vec![3, 4])));
+//! assert!(cmd.parse_s("jump").is_err());
+//!
+//! let mut named: HashMap<&str, RcParser<String>> = HashMap::new();
+//! named.insert("word", rc_parser(string(Alpha.plus())));
+//! named.insert("num", rc_parser(string(NumDigit.plus())));
+//! let p = (named["word"].clone(), ws_(named["num"].clone()));
+//! assert_eq!(p.parse_s("abc 123"), Ok(("abc".to_string(), "123".to_string())));
+//! ```
+use crate::err::*;
+use crate::hooks::trace_combi;
+use crate::iter::*;
+use crate::parser::*;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// An object safe version of [Parser]
+pub trait DynParser<'a, O> {
+    fn dyn_parse(&self, it: &PIter<'a>) -> ParseRes<'a, O>;
+}
+
+impl<'a, P: Parser<'a>> DynParser<'a, P::Out> for P {
+    fn dyn_parse(&self, it: &PIter<'a>) -> ParseRes<'a, P::Out> {
+        self.parse(it)
+    }
+}
+
+pub type BoxParser<'a, O> = Box<dyn DynParser<'a, O> + 'a>;
+pub type RcParser<'a, O> = Rc<dyn DynParser<'a, O> + 'a>;
+pub type ArcParser<'a, O> = Arc<dyn DynParser<'a, O> + Send + Sync + 'a>;
+
+impl<'a, 'p, O> Parser<'a> for Box<dyn DynParser<'a, O> + 'p> {
+    type Out = O;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, O> {
+        (**self).dyn_parse(it)
+    }
+}
+
+impl<'a, 'p, O> Parser<'a> for Rc<dyn DynParser<'a, O> + 'p> {
+    type Out = O;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, O> {
+        (**self).dyn_parse(it)
+    }
+}
+
+impl<'a, 'p, O> Parser<'a> for Arc<dyn DynParser<'a, O> + Send + Sync + 'p> {
+    type Out = O;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, O> {
+        (**self).dyn_parse(it)
+    }
+}
+
+pub fn rc_parser<'a, P: Parser<'a> + 'a>(p: P) -> RcParser<'a, P::Out> {
+    Rc::new(p)
+}
+
+/// A parser that can be shared between threads
+pub fn arc_parser<'a, P: Parser<'a> + Send + Sync + 'a>(p: P) -> ArcParser<'a, P::Out> {
+    Arc::new(p)
+}
+
+/// Tries each parser in turn, like a chain of [crate::or] built at runtime
+pub struct OrList<P> {
+    list: Vec<P>,
+}
+
+impl<'a, P: Parser<'a>> Parser<'a> for OrList<P> {
+    type Out = P::Out;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, P::Out> {
+        trace_combi("or_list", it, || {
+            let mut err: Option<PErr<'a>> = None;
+            for p in &self.list {
+                match p.parse(it) {
+                    Ok(r) => return Ok(r),
+                    Err(e) if e.is_break => return Err(e),
+                    Err(e) => {
+                        err = Some(match err {
+                            Some(e2) => e2.longer(e),
+                            None => e,
+                        })
+                    }
+                }
+            }
+            Err(err.unwrap_or_else(|| it.err(Expected::Nil)))
+        })
+    }
+}
+
+pub fn or_list<'a, P: Parser<'a>>(list: Vec<P>) -> OrList<P> {
+    OrList { list }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    struct Grammar<'a> {
+        item: ArcParser<'a, &'a str>,
+    }
+
+    #[test]
+    fn test_stored_parsers() {
+        let g = Grammar {
+            item: arc_parser(or("cat", "dog")),
+        };
+        let g2 = Grammar {
+            item: g.item.clone(),
+        };
+        let p = sep_plus(g2.item.clone(), ',');
+        assert_eq!(p.parse_s("cat,dog"), Ok(vec!["cat", "dog"]));
+        let e = or_list::<BoxParser<()>>(vec![]).parse_s("x").unwrap_err();
+        assert_eq!(e.exp, Expected::Nil);
+        std::thread::spawn(move || assert!(g.item.parse_s("dog").is_ok()))
+            .join()
+            .unwrap();
+    }
+}
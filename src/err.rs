@@ -1,3 +1,4 @@
+use crate::source::FileId;
 use std::cmp::Ordering;
 use std::fmt;
 
@@ -9,8 +10,12 @@ pub enum Expected {
     Char(char),
     CharIn(&'static str),
     Str(&'static str),
+    Byte(u8),
+    Bytes(&'static [u8]),
     OneOf(Vec<Expected>),
     Keyword(Box<Expected>),
+    /// A message built at parse time, usually by [crate::Parser::verify]
+    Msg(String),
 }
 impl Expected {
     pub fn join(self, b: Self) -> Self {
@@ -48,7 +53,10 @@ impl fmt::Display for Expected {
             Expected::Char(c) => write!(f, "{}", c),
             Expected::CharIn(s) => write!(f, "Char In '{}'", s),
             Expected::Str(s) => write!(f, "{}", s),
+            Expected::Byte(b) => write!(f, "0x{:02x}", b),
+            Expected::Bytes(b) => write!(f, "b\"{}\"", b.escape_ascii()),
             Expected::Keyword(s) => write!(f, "keyword {}", s),
+            Expected::Msg(s) => write!(f, "{}", s),
             Expected::OneOf(v) => {
                 write!(f, "One of [")?;
                 let mut coma = "";
@@ -62,15 +70,25 @@ impl fmt::Display for Expected {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PErr<'a> {
     pub exp: Expected,
     pub found: &'a str,
     pub line: usize,
     pub col: usize,
     pub index: Option<usize>,
+    /// Where the text the error covers finishes, None for the end of input.
+    /// This is the char at index, or further if the parser read more before failing,
+    /// as a string literal or a [crate::verify] does
+    pub end: Option<usize>,
     pub is_break: bool,
+    /// Set for breaks made by a [crate::Cut], which [crate::maybe] and the repeaters pass on
+    pub is_cut: bool,
     pub child: Option<Box<Self>>,
+    pub file: Option<FileId>,
+    /// Rarely set, so boxed to keep PErr under clippy's result_large_err size. See [PErr::context]
+    #[allow(clippy::box_collection)]
+    pub(crate) context: Option<Box<Vec<&'static str>>>,
 }
 fn compare_index(a: &Option<usize>, b: &Option<usize>) -> Ordering {
     match (a, b) {
@@ -81,6 +99,13 @@ fn compare_index(a: &Option<usize>, b: &Option<usize>) -> Ordering {
     }
 }
 
+fn max_end(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match compare_index(&a, &b) {
+        Ordering::Less => b,
+        _ => a,
+    }
+}
+
 fn join_children<'a>(a: Option<Box<PErr<'a>>>, b: Option<Box<PErr<'a>>>) -> Option<Box<PErr<'a>>> {
     match (a, b) {
         (Some(ac), Some(bc)) => Some(Box::new((*ac).join(*bc))),
@@ -89,13 +114,21 @@ fn join_children<'a>(a: Option<Box<PErr<'a>>>, b: Option<Box<PErr<'a>>>) -> Opti
     }
 }
 
-fn read_10<'a>(s: &'a str) -> &'a str {
+fn read_10(s: &str) -> &str {
     match s.char_indices().take(10).last() {
         Some((n, _)) => &s[..n],
         None => "EOI",
     }
 }
 
+/// "while parsing a > b: " for non empty contexts
+fn write_context(f: &mut fmt::Formatter, ctx: &[&'static str]) -> fmt::Result {
+    if ctx.is_empty() {
+        return Ok(());
+    }
+    write!(f, "while parsing {}: ", ctx.join(" > "))
+}
+
 impl<'a> std::error::Error for PErr<'a> {}
 impl<'a> fmt::Display for PErr<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -103,6 +136,7 @@ impl<'a> fmt::Display for PErr<'a> {
             Some(n) => n.to_string(),
             None => "EOI".to_string(),
         };
+        write_context(f, self.context())?;
         write!(
             f,
             "Expected '{}', Found '{}', at (i:{},l:{},c:{})",
@@ -126,6 +160,7 @@ impl<'a> PErr<'a> {
             _ => {
                 self.child = join_children(self.child, b.child);
                 self.exp = self.exp.join(b.exp);
+                self.end = max_end(self.end, b.end);
                 self
             }
         }
@@ -136,6 +171,39 @@ impl<'a> PErr<'a> {
         self
     }
 
+    /// A break that is not hidden by [crate::maybe] or the repeaters
+    pub fn cut(mut self) -> Self {
+        self.is_break = true;
+        self.is_cut = true;
+        self
+    }
+
+    /// Sets the end of the text the error covers
+    pub fn with_end(mut self, end: Option<usize>) -> Self {
+        self.end = end;
+        self
+    }
+
+    /// Extends the error to cover the text up to end, if it does not already
+    pub fn cover_to(mut self, end: Option<usize>) -> Self {
+        self.end = max_end(self.end, end);
+        self
+    }
+
+    /// Records that the error happened inside the named rule
+    pub fn in_context(mut self, s: &'static str) -> Self {
+        self.context.get_or_insert_with(Box::default).insert(0, s);
+        self
+    }
+
+    /// The rules being parsed when the error happened, outermost first
+    pub fn context(&self) -> &[&'static str] {
+        match &self.context {
+            Some(c) => c,
+            None => &[],
+        }
+    }
+
     pub fn join(mut self, mut b: Self) -> Self {
         match compare_index(&self.index, &b.index) {
             Ordering::Greater => {
@@ -149,6 +217,7 @@ impl<'a> PErr<'a> {
             _ => {
                 self.child = join_children(self.child, b.child);
                 self.exp = self.exp.join(b.exp);
+                self.end = max_end(self.end, b.end);
                 self
             }
         }
@@ -160,8 +229,11 @@ impl<'a> PErr<'a> {
             line: self.line,
             col: self.col,
             index: self.index,
+            end: self.end,
             is_break: self.is_break,
             child: self.child.map(|v| Box::new((*v).strung())),
+            file: self.file,
+            context: self.context.map(|c| *c).unwrap_or_default(),
         }
     }
 }
@@ -171,10 +243,13 @@ pub struct StrungError {
     pub exp: Expected,
     pub found: String,
     pub index: Option<usize>,
+    pub end: Option<usize>,
     pub line: usize,
     pub col: usize,
     pub is_break: bool,
     pub child: Option<Box<StrungError>>,
+    pub file: Option<FileId>,
+    pub context: Vec<&'static str>,
 }
 impl std::error::Error for StrungError {}
 
@@ -184,9 +259,10 @@ impl fmt::Debug for StrungError {
             Some(n) => n.to_string(),
             None => "EOI".to_string(),
         };
-        write!(
+        write_context(f, &self.context)?;
+        writeln!(
             f,
-            "Expected '{}', Found '{}', at (i={},l={},c={})\n",
+            "Expected '{}', Found '{}', at (i={},l={},c={})",
             self.exp, self.found, i_str, self.line, self.col
         )
     }
@@ -197,9 +273,10 @@ impl fmt::Display for StrungError {
             Some(n) => n.to_string(),
             None => "EOI".to_string(),
         };
-        write!(
+        write_context(f, &self.context)?;
+        writeln!(
             f,
-            "Expected '{}', Found '{}', at (i={},l={},c={})\n",
+            "Expected '{}', Found '{}', at (i={},l={},c={})",
             self.exp, self.found, i_str, self.line, self.col
         )?;
         if let Some(ref c) = self.child {
@@ -0,0 +1,84 @@
+//! The calls [parser!] and the choice combinators make, so tracing and profiling can see
+//! them. Without the `trace` feature these only run the parser.
+use crate::iter::*;
+use crate::parser::*;
+#[cfg(feature = "trace")]
+use std::cell::Cell;
+
+#[cfg(feature = "trace")]
+thread_local! {
+    /// The trace level + 1 in the LEVEL bits, and PROFILING, so a rule only reads one cell
+    /// when neither is running
+    static HOOKS: Cell<u8> = const { Cell::new(0) };
+}
+
+#[cfg(feature = "trace")]
+pub(crate) const LEVEL: u8 = 3;
+#[cfg(feature = "trace")]
+pub(crate) const PROFILING: u8 = 4;
+
+#[cfg(feature = "trace")]
+pub(crate) fn hooks() -> u8 {
+    HOOKS.with(|h| h.get())
+}
+
+/// Sets the bits in mask to those in val, returning the old bits under the mask
+#[cfg(feature = "trace")]
+pub(crate) fn set_hooks(mask: u8, val: u8) -> u8 {
+    HOOKS.with(|h| {
+        let old = h.get();
+        h.set((old & !mask) | (val & mask));
+        old & mask
+    })
+}
+
+/// Records a node for a rule while tracing, and counts it while profiling, used by [parser!]
+#[cfg(feature = "trace")]
+pub fn trace_rule<'a, V, F: FnOnce() -> ParseRes<'a, V>>(
+    name: &'static str,
+    it: &PIter<'a>,
+    f: F,
+) -> ParseRes<'a, V> {
+    use crate::profile::profile_rule;
+    use crate::trace::record;
+    let h = hooks();
+    match (h & LEVEL > 0, h & PROFILING > 0) {
+        (false, false) => f(),
+        (true, false) => record(name, it, f),
+        (false, true) => profile_rule(name, it, f),
+        (true, true) => record(name, it, || profile_rule(name, it, f)),
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+pub fn trace_rule<'a, V, F: FnOnce() -> ParseRes<'a, V>>(
+    _name: &'static str,
+    _it: &PIter<'a>,
+    f: F,
+) -> ParseRes<'a, V> {
+    f()
+}
+
+/// As [trace_rule], only recorded at TraceLevel::All, and not profiled
+#[cfg(feature = "trace")]
+pub fn trace_combi<'a, V, F: FnOnce() -> ParseRes<'a, V>>(
+    name: &'static str,
+    it: &PIter<'a>,
+    f: F,
+) -> ParseRes<'a, V> {
+    match hooks() & LEVEL > crate::trace::TraceLevel::Rules as u8 + 1 {
+        true => crate::trace::record(name, it, f),
+        false => f(),
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+pub fn trace_combi<'a, V, F: FnOnce() -> ParseRes<'a, V>>(
+    _name: &'static str,
+    _it: &PIter<'a>,
+    f: F,
+) -> ParseRes<'a, V> {
+    f()
+}
@@ -0,0 +1,286 @@
+//! Layout combinators for indentation sensitive grammars.
+//!
+//! [indented_block] reads lines indented deeper than the one it started on. The iterator
+//! carries the indentation of the innermost block, see [PIter::indent], so nested blocks,
+//! [same_indent] and [aligned] know what they are inside. Indentation is measured in columns
+//! and lines are split using the iterator's [LcConf], so tab widths and line endings are
+//! respected. Blank lines are skipped.
+//!
+//! ```rust
+//! use bogobble::*;
+//! #[derive(Debug, PartialEq)]
+//! pub enum Node {
+//!     Leaf(String, isize),
+//!     Tree(String, Vec<Node>),
+//! }
+//! parser! {(Entry->Node)
+//!     (string(Alpha.plus()), ':', or(
+//!         last(' ', common::Int).map(|n| (Some(n), vec![])),
+//!         indented_block(Entry).map(|v| (None, v)),
+//!     )).map(|(k, _, (n, v))| match n {
+//!         Some(n) => Node::Leaf(k, n),
+//!         None => Node::Tree(k, v),
+//!     })
+//! }
+//!
+//! let s = "a:\n  b: 1\n\n  c:\n    d: 2\n  e: 3\nf: 4";
+//! let v = star(same_indent(Entry)).parse_s(s).unwrap();
+//! assert_eq!(v.len(), 2);
+//! match &v[0] {
+//!     Node::Tree(_, ch) => assert_eq!(ch[2], Node::Leaf("e".to_string(), 3)),
+//!     _ => panic!("Expected tree"),
+//! }
+//!
+//! let e = Entry.parse_s("a:\n    b: 1\n  c: 2").unwrap_err();
+//! assert_eq!(e.exp, Expected::Str("dedent to an enclosing indentation"));
+//! assert_eq!((e.line, e.col), (2, 2));
+//! ```
+use crate::err::*;
+use crate::iter::*;
+use crate::parser::*;
+
+/// Skips spaces and tabs, returning the iterator after them and the column reached from col
+fn skip_indent<'a>(it: &PIter<'a>, mut col: usize) -> (PIter<'a>, usize) {
+    let conf = it.conf();
+    let mut it = *it;
+    loop {
+        let mut s = it.as_str().chars();
+        match s.next() {
+            Some(c) if c == ' ' || c == '\t' => {
+                col = conf.step((0, col), c, s.next()).1;
+                it.next();
+            }
+            _ => return (it, col),
+        }
+    }
+}
+
+/// The length of the line end at the start of s, if there is one
+fn line_end_len(s: &str, ends: LineEnds) -> Option<usize> {
+    match s.as_bytes() {
+        [b'\n', ..] => Some(1),
+        [b'\r', b'\n', ..] => Some(2),
+        [b'\r', ..] if ends == LineEnds::All => Some(1),
+        _ => None,
+    }
+}
+
+/// The offset of the start of the line containing pos
+fn line_start(s: &str, pos: usize, ends: LineEnds) -> usize {
+    let before = &s[..pos];
+    let found = match ends {
+        LineEnds::All => before.rfind(['\n', '\r']),
+        _ => before.rfind('\n'),
+    };
+    found.map(|n| n + 1).unwrap_or(0)
+}
+
+fn at_line_start(it: &PIter) -> bool {
+    let (s, pos) = (it.orig_str(), it.offset());
+    // between the two chars of a "\r\n" is still on the line before
+    line_start(s, pos, it.conf().ends) == pos
+        && !(s[..pos].ends_with('\r') && s[pos..].starts_with('\n'))
+}
+
+/// The column of the iterator counted from the start of its line
+fn line_col_of(it: &PIter) -> usize {
+    let conf = it.conf();
+    let pos = it.offset();
+    let line = &it.orig_str()[line_start(it.orig_str(), pos, conf.ends)..pos];
+    let mut chars = line.chars().peekable();
+    let mut col = 0;
+    while let Some(c) = chars.next() {
+        col = conf.step((0, col), c, chars.peek().copied()).1;
+    }
+    col
+}
+
+/// The indentation of the line the iterator is on
+fn line_indent_of(it: &PIter) -> usize {
+    let start = line_start(it.orig_str(), it.offset(), it.conf().ends);
+    skip_indent(&PIter::with_conf(&it.orig_str()[start..], it.conf()), 0).1
+}
+
+/// Moves past the line end at the iterator, if there is one
+fn skip_line_end<'a>(it: &PIter<'a>) -> Option<PIter<'a>> {
+    let n = line_end_len(it.as_str(), it.conf().ends)?;
+    let mut it = *it;
+    for _ in 0..n {
+        it.next();
+    }
+    Some(it)
+}
+
+/// From the start of a line, skips blank lines.
+/// Returns the start of the next line with content, the iterator after its indentation,
+/// and the indentation
+fn next_line<'a>(it: &PIter<'a>) -> (PIter<'a>, PIter<'a>, usize) {
+    let mut it = *it;
+    loop {
+        let (ni, col) = skip_indent(&it, 0);
+        match skip_line_end(&ni) {
+            Some(ni) => it = ni,
+            None => return (it, ni, col),
+        }
+    }
+}
+
+/// Moves past the end of the current line, if nothing is left on it
+fn end_line<'a>(it: &PIter<'a>) -> Result<PIter<'a>, PErr<'a>> {
+    if at_line_start(it) {
+        return Ok(*it);
+    }
+    let (ni, _) = skip_indent(it, 0);
+    if ni.eoi() {
+        return Ok(ni);
+    }
+    match skip_line_end(&ni) {
+        Some(ni) => Ok(ni),
+        None => ni.err_r(Expected::Str("end of line")),
+    }
+}
+
+pub struct IndentedBlock<P> {
+    p: P,
+}
+
+impl<'a, P: Parser<'a>> Parser<'a> for IndentedBlock<P> {
+    type Out = Vec<P::Out>;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
+        let parent = it.indent();
+        let outer = match at_line_start(it) {
+            true => parent,
+            false => parent.max(line_indent_of(it)),
+        };
+        let it = end_line(it)?;
+        let (_, ci, col) = next_line(&it);
+        if ci.eoi() || col <= outer {
+            return ci.err_r(Expected::Str("an indented block"));
+        }
+        let mut it = it.with_indent(col);
+        let mut res = Vec::new();
+        loop {
+            let (ls, ci, c) = next_line(&it);
+            if ci.eoi() {
+                return Ok((ci.with_indent(parent), res, None));
+            }
+            if c > col {
+                return ci.err_r(Expected::Str("indentation matching the block"));
+            }
+            if c < col {
+                // a dedent below the parent is checked by the parent
+                if c > parent {
+                    return ci.err_r(Expected::Str("dedent to an enclosing indentation"));
+                }
+                return Ok((ls.with_indent(parent), res, None));
+            }
+            let (ni, v, _) = self.p.parse(&ci)?;
+            res.push(v);
+            it = end_line(&ni)?;
+        }
+    }
+}
+
+/// Parses one or more lines with p, each indented by the same amount, and deeper than
+/// the enclosing block and the line the block starts on.
+/// Anything after p on a line is an error. The block ends on a line indented less,
+/// which must match an enclosing block.
+pub fn indented_block<'a, P: Parser<'a>>(p: P) -> IndentedBlock<P> {
+    IndentedBlock { p }
+}
+
+pub struct SameIndent<P> {
+    p: P,
+}
+
+impl<'a, P: Parser<'a>> Parser<'a> for SameIndent<P> {
+    type Out = P::Out;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, P::Out> {
+        let it = end_line(it)?;
+        let (_, ci, col) = next_line(&it);
+        if col != it.indent() {
+            return ci.err_r(Expected::Str("the same indentation"));
+        }
+        self.p.parse(&ci)
+    }
+}
+
+/// Moves to the next line with content, and parses p there if it is at the indentation of
+/// the current block. Useful for the top level, and for lines following an [aligned] item.
+pub fn same_indent<'a, P: Parser<'a>>(p: P) -> SameIndent<P> {
+    SameIndent { p }
+}
+
+pub struct Aligned<P> {
+    p: P,
+}
+
+impl<'a, P: Parser<'a>> Parser<'a> for Aligned<P> {
+    type Out = P::Out;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, P::Out> {
+        let (r, v, ex) = self.p.parse(&it.with_indent(line_col_of(it)))?;
+        Ok((r.with_indent(it.indent()), v, ex))
+    }
+}
+
+/// Runs p with the current column as the block indentation,
+/// so lines inside p can line up with where it started.
+///
+/// ```rust
+/// use bogobble::*;
+/// let binding = (Alpha.plus(), " = ", common::Int).map(|(k, _, v)| (k, v));
+/// let p = last("let ", aligned((binding.br(), star(same_indent(binding.br())))));
+/// let (a, rest) = p.parse_s("let x = 1\n    y = 2\n\n    z = 3\n in").unwrap();
+/// assert_eq!(a, ("x", 1));
+/// assert_eq!(rest, vec![("y", 2), ("z", 3)]);
+/// assert!(p.parse_s("let x = 1\n   y = 2").unwrap().1.is_empty());
+/// ```
+pub fn aligned<'a, P: Parser<'a>>(p: P) -> Aligned<P> {
+    Aligned { p }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_tabs_and_errors() {
+        let line = (Alpha.plus(), ';');
+        let p = ("if:", indented_block(line.br()));
+        let conf = LcConf::new().tab_width(4);
+        let (_, (_, v), _) = p
+            .parse(&PIter::with_conf("if:\n\ta;\n    b;\nc", conf))
+            .unwrap();
+        assert_eq!(v, vec![("a", ';'), ("b", ';')]);
+
+        let e = p.parse_s("if:\n  a;\n    b;").unwrap_err();
+        assert_eq!(e.exp, Expected::Str("indentation matching the block"));
+        let e = p.parse_s("if: x\n  a;").unwrap_err();
+        assert_eq!(e.exp, Expected::Str("end of line"));
+        let e = p.parse_s("if:\nb;").unwrap_err();
+        assert_eq!(e.exp, Expected::Str("an indented block"));
+        let e = p.parse_s("if:\n  a; b;").unwrap_err();
+        assert_eq!((e.exp, e.col), (Expected::Str("end of line"), 5));
+        let (r, _, _) = p.parse(&PIter::new("if:\n  a;\nb;")).unwrap();
+        assert_eq!((r.index(), r.indent()), (Some(9), 0));
+    }
+
+    #[test]
+    fn test_lone_cr_lines() {
+        let line = (Alpha.plus(), ';');
+        let p = ("if:", indented_block(line.br()));
+        let all = LcConf::new().line_ends(LineEnds::All);
+        let (_, (_, v), _) = p
+            .parse(&PIter::with_conf("if:\r  a;\r\r  b;\r\nc", all))
+            .unwrap();
+        assert_eq!(v, vec![("a", ';'), ("b", ';')]);
+        let e = p
+            .parse(&PIter::with_conf("if:\r  a;\r    b;", all))
+            .unwrap_err();
+        assert_eq!(e.exp, Expected::Str("indentation matching the block"));
+        assert_eq!((e.line, e.col), (2, 4));
+        // without LineEnds::All a lone '\r' is not a line end
+        let e = p.parse_s("if:\r  a;\r  b;").unwrap_err();
+        assert_eq!(e.exp, Expected::Str("end of line"));
+    }
+}
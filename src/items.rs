@@ -0,0 +1,117 @@
+//! Parsing a run of top level items lazily, one at a time.
+//!
+//! [Parser::parse_iter] gives an iterator that parses the next item each time it is called,
+//! instead of building a `Vec` of them all as [crate::star] does. It stops at the end of input.
+//! By default it also stops after the first error, unless given a [ParseIter::resync] point
+//! to skip to and carry on from.
+//!
+//! ```rust
+//! use bogobble::*;
+//! let line = (string(Alpha.plus()), ws_(common::Int), '\n').map(|(k, n, _)| (k, n));
+//! let log = "start 1\nstop x\ngo 3\n";
+//!
+//! let mut items = line.parse_iter(log);
+//! assert_eq!(items.next(), Some(Ok(("start".to_string(), 1))));
+//! assert!(items.next().unwrap().is_err());
+//! assert_eq!(items.next(), None);
+//!
+//! let res: Vec<_> = line.parse_iter(log).resync('\n').collect();
+//! assert_eq!(res.len(), 3);
+//! assert_eq!(res[1].as_ref().unwrap_err().line, 1);
+//! assert_eq!(res[2], Ok(("go".to_string(), 3)));
+//! ```
+use crate::dynamic::BoxParser;
+use crate::err::*;
+use crate::iter::*;
+use crate::parser::*;
+use crate::recover::skip_past;
+
+/// The iterator from [Parser::parse_iter]
+pub struct ParseIter<'p, 'a, P> {
+    p: &'p P,
+    it: PIter<'a>,
+    resync: Option<BoxParser<'a, &'a str>>,
+    done: bool,
+}
+
+impl<'p, 'a, P: Parser<'a>> ParseIter<'p, 'a, P> {
+    /// Items from an iterator, so its position, file and line settings are used
+    pub fn new(p: &'p P, it: &PIter<'a>) -> Self {
+        ParseIter {
+            p,
+            it: *it,
+            resync: None,
+            done: false,
+        }
+    }
+
+    /// After an error, skip past the next match of sync, then continue with the next item
+    pub fn resync<S: Parser<'a> + 'a>(mut self, sync: S) -> Self {
+        self.resync = Some(skip_past(sync).boxed());
+        self
+    }
+
+    /// Where the next item will be parsed from
+    pub fn iter(&self) -> &PIter<'a> {
+        &self.it
+    }
+
+    fn skip(&mut self) {
+        let r = match &self.resync {
+            Some(s) => s.parse(&self.it),
+            None => {
+                self.done = true;
+                return;
+            }
+        };
+        match r {
+            Ok((r, _, _)) if r.index() != self.it.index() => self.it = r,
+            _ => self.done = true,
+        }
+    }
+}
+
+impl<'p, 'a, P: Parser<'a>> Iterator for ParseIter<'p, 'a, P> {
+    type Item = Result<P::Out, PErr<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.it.eoi() {
+            return None;
+        }
+        match self.p.parse(&self.it) {
+            Ok((r, _, _)) if r.index() == self.it.index() => {
+                self.done = true;
+                Some(Err(r.err_s("To Consume some Data")))
+            }
+            Ok((r, v, _)) => {
+                self.it = r;
+                Some(Ok(v))
+            }
+            Err(e) => {
+                self.skip();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_parse_iter_stops() {
+        let p = first(common::Int, ';');
+        let res: Vec<_> = p.parse_iter("1;x;3;").resync(';').collect();
+        assert_eq!(res.len(), 3);
+        assert_eq!(res[2], Ok(3));
+        // nothing to resync to at the end
+        let res: Vec<_> = p.parse_iter("1;2;3").resync(';').collect();
+        assert_eq!(res.len(), 3);
+        assert_eq!(res[2].as_ref().unwrap_err().index, None);
+        // an item that consumes nothing would never end
+        let p = maybe('a');
+        let mut it = p.parse_iter("b").resync(';');
+        assert!(it.next().unwrap().is_err());
+        assert!(it.next().is_none());
+    }
+}
@@ -1,49 +1,308 @@
 use crate::err::*;
 use crate::parser::*;
-use std::str::CharIndices;
+use crate::source::FileId;
+use std::cell::Cell;
 
-#[derive(Debug, Clone)]
+/// How columns are counted for errors and positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColMode {
+    /// One per unicode scalar value
+    #[default]
+    Chars,
+    /// UTF-16 code units, as used by LSP
+    Utf16,
+    /// Terminal display width, wide chars count 2 and combining marks 0.
+    Width,
+}
+
+impl ColMode {
+    pub fn char_cols(&self, c: char) -> usize {
+        match self {
+            ColMode::Chars => 1,
+            ColMode::Utf16 => c.len_utf16(),
+            ColMode::Width => display_width(c),
+        }
+    }
+
+    pub fn str_cols(&self, s: &str) -> usize {
+        s.chars().map(|c| self.char_cols(c)).sum()
+    }
+}
+
+/// An approximation of the terminal width of a char, without the full unicode tables.
+pub fn display_width(c: char) -> usize {
+    match c as u32 {
+        0..=0x1f | 0x7f..=0x9f => 0,
+        0x300..=0x36f
+        | 0x483..=0x489
+        | 0x591..=0x5bd
+        | 0x610..=0x61a
+        | 0x64b..=0x65f
+        | 0x1ab0..=0x1aff
+        | 0x1dc0..=0x1dff
+        | 0x200b..=0x200f
+        | 0x20d0..=0x20ff
+        | 0xfe00..=0xfe0f
+        | 0xfe20..=0xfe2f
+        | 0xe0100..=0xe01ef => 0,
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x2fffd
+        | 0x30000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+/// Which chars end a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineEnds {
+    /// Only '\n', a '\r' is counted as a normal char
+    #[default]
+    Lf,
+    /// '\n', with a '\r' before it taking no columns
+    CrLf,
+    /// '\n', "\r\n" and a lone '\r' each end one line
+    All,
+}
+
+/// The settings used to count lines and columns while parsing
+///
+/// ```rust
+/// use bogobble::*;
+/// let conf = LcConf::new().tab_width(4).line_ends(LineEnds::CrLf);
+/// let p = last(WSL.istar(), line_col);
+/// assert_eq!(p.parse(&PIter::with_conf("\r\n\t ", conf)).unwrap().1, (1, 5));
+/// assert_eq!(p.parse(&PIter::new("\r\n\t ")).unwrap().1, (1, 2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LcConf {
+    pub cols: ColMode,
+    /// Tabs move to the next multiple of this, 1 counts a tab as one column, as does 0
+    pub tab: usize,
+    pub ends: LineEnds,
+    /// When false lines and columns are not counted during the parse,
+    /// use a [crate::LineIndex] to find them afterwards.
+    pub track: bool,
+}
+
+impl Default for LcConf {
+    fn default() -> Self {
+        LcConf {
+            cols: ColMode::Chars,
+            tab: 1,
+            ends: LineEnds::Lf,
+            track: true,
+        }
+    }
+}
+
+impl LcConf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cols(mut self, cols: ColMode) -> Self {
+        self.cols = cols;
+        self
+    }
+
+    pub fn tab_width(mut self, tab: usize) -> Self {
+        self.tab = tab.max(1);
+        self
+    }
+
+    pub fn line_ends(mut self, ends: LineEnds) -> Self {
+        self.ends = ends;
+        self
+    }
+
+    pub fn untracked(mut self) -> Self {
+        self.track = false;
+        self
+    }
+
+    /// The line and column after `c`, `next` is the char following it.
+    pub fn step(&self, (l, col): (usize, usize), c: char, next: Option<char>) -> (usize, usize) {
+        match (c, self.ends) {
+            ('\n', _) => (l + 1, 0),
+            ('\r', LineEnds::CrLf) | ('\r', LineEnds::All) if next == Some('\n') => (l, col),
+            ('\r', LineEnds::All) => (l + 1, 0),
+            ('\t', _) => {
+                let tab = self.tab.max(1);
+                (l, (col / tab + 1) * tab)
+            }
+            _ => (l, col + self.cols.char_cols(c)),
+        }
+    }
+}
+
+/// The position in a string being parsed.
+/// This is small and Copy, so backtracking is just keeping an old one.
+#[derive(Debug, Clone, Copy)]
 pub struct PIter<'a> {
     orig: &'a str,
-    it: CharIndices<'a>,
+    pos: usize,
     l: usize,
     c: usize,
+    conf: LcConf,
+    file: Option<FileId>,
+    /// Set when a parser tries to read past the end, so a stream knows the window was too short
+    edge: Option<&'a Cell<bool>>,
+    /// The indentation of the innermost layout block, see [crate::indented_block]
+    indent: usize,
 }
 
 impl<'a> PIter<'a> {
     pub fn new(s: &'a str) -> Self {
+        Self::with_conf(s, LcConf::default())
+    }
+
+    /// ```rust
+    /// use bogobble::*;
+    /// let s = "日本 x";
+    /// let p = last(Any.except("x").star(), line_col);
+    /// assert_eq!(p.parse(&PIter::new(s)).unwrap().1, (0, 3));
+    /// assert_eq!(p.parse(&PIter::with_cols(s, ColMode::Width)).unwrap().1, (0, 5));
+    /// ```
+    pub fn with_cols(s: &'a str, cols: ColMode) -> Self {
+        Self::with_conf(s, LcConf::new().cols(cols))
+    }
+
+    pub fn with_conf(s: &'a str, conf: LcConf) -> Self {
         PIter {
             orig: s,
-            it: s.char_indices(),
+            pos: 0,
             l: 0,
             c: 0,
+            conf,
+            file: None,
+            edge: None,
+            indent: 0,
         }
     }
 
+    /// Mark errors and positions from this iterator as coming from the file,
+    /// Normally called by [crate::SourceMap::iter]
+    pub fn in_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    pub fn file(&self) -> Option<FileId> {
+        self.file
+    }
+
+    /// Start parsing part way through a string, counting lines and columns up to that point
+    ///
+    /// Panics if offset is not on a char boundary or is past the end of the string.
+    ///
+    /// ```rust
+    /// use bogobble::*;
+    /// let s = "hello\n  world";
+    /// let it = PIter::at(s, 8);
+    /// assert_eq!(it.lc(), (1, 2));
+    /// assert_eq!(it.index(), Some(8));
+    /// let e = (Alpha.plus(), "!").parse(&it).unwrap_err();
+    /// assert_eq!((e.index, e.line, e.col), (None, 1, 7));
+    /// ```
+    pub fn at(s: &'a str, offset: usize) -> Self {
+        Self::at_conf(s, offset, LcConf::default())
+    }
+
+    pub fn at_conf(s: &'a str, offset: usize, conf: LcConf) -> Self {
+        assert!(
+            s.is_char_boundary(offset),
+            "PIter::at offset {} not on a char boundary",
+            offset
+        );
+        let mut it = Self::with_conf(s, conf);
+        while it.pos < offset {
+            it.next();
+        }
+        it
+    }
+
+    /// Used when the string is a part of a larger input to continue the line count
+    pub(crate) fn with_lc(mut self, (l, c): (usize, usize)) -> Self {
+        self.l = l;
+        self.c = c;
+        self
+    }
+
+    /// Sets `edge` whenever this iterator, or any copy of it, reads at the end of the string
+    pub(crate) fn watch_edge(mut self, edge: &'a Cell<bool>) -> Self {
+        self.edge = Some(edge);
+        self
+    }
+
+    /// The indentation of the innermost [crate::indented_block] or [crate::aligned] being
+    /// parsed, 0 outside of any
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    pub(crate) fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    fn at_edge(&self) {
+        if let Some(e) = self.edge {
+            e.set(true);
+        }
+    }
+
+    pub fn col_mode(&self) -> ColMode {
+        self.conf.cols
+    }
+
+    pub fn conf(&self) -> LcConf {
+        self.conf
+    }
+
     pub fn orig_str(&self) -> &'a str {
         self.orig
     }
 
     pub fn as_str(&self) -> &'a str {
-        self.it.as_str()
+        &self.orig[self.pos..]
     }
 
     pub fn str_to(&self, id: Option<usize>) -> &'a str {
         match (self.index(), id) {
-            (Some(s), Some(f)) => &self.it.as_str()[..(f - s)],
-            _ => self.it.as_str(),
+            (Some(s), Some(f)) => &self.orig[s..f],
+            _ => self.as_str(),
         }
     }
 
     pub fn err(&self, exp: Expected) -> PErr<'a> {
         PErr {
             exp,
-            found: self.it.as_str(),
+            found: self.as_str(),
             index: self.index(),
+            end: self
+                .as_str()
+                .chars()
+                .next()
+                .map(|c| self.pos + c.len_utf8()),
             line: self.l,
             col: self.c,
             is_break: false,
+            is_cut: false,
             child: None,
+            file: self.file,
+            context: None,
         }
     }
 
@@ -63,14 +322,35 @@ impl<'a> PIter<'a> {
         (self.l, self.c)
     }
     pub fn index(&self) -> Option<usize> {
-        self.it.clone().next().map(|(i, _)| i)
+        match self.pos < self.orig.len() {
+            true => Some(self.pos),
+            false => None,
+        }
     }
 
     pub fn next_i(&mut self) -> Option<(usize, char)> {
-        self.it.next()
+        let c = match self.as_str().chars().next() {
+            Some(c) => c,
+            None => {
+                self.at_edge();
+                return None;
+            }
+        };
+        let i = self.pos;
+        self.pos += c.len_utf8();
+        Some((i, c))
+    }
+    /// The byte offset, unlike [PIter::index] this is the length of the input at the end
+    pub fn offset(&self) -> usize {
+        self.pos.min(self.orig.len())
     }
+
     pub fn eoi(&self) -> bool {
-        self.it.clone().next().is_none()
+        let r = self.pos >= self.orig.len();
+        if r {
+            self.at_edge();
+        }
+        r
     }
 }
 
@@ -78,25 +358,83 @@ impl<'a> Iterator for PIter<'a> {
     type Item = char;
     fn next(&mut self) -> Option<char> {
         //println!("lc {} {} ", self.l, self.c);
-        match self.it.next() {
-            Some((_, '\n')) => {
-                self.l += 1;
-                self.c = 0;
-                Some('\n')
+        let v = match self.as_str().chars().next() {
+            Some(v) => v,
+            None => {
+                self.at_edge();
+                return None;
             }
-            Some((_, v)) => {
-                self.c += 1;
-                Some(v)
-            }
-            None => None,
+        };
+        self.pos += v.len_utf8();
+        if !self.conf.track {
+            return Some(v);
         }
+        let (l, c) = self
+            .conf
+            .step((self.l, self.c), v, self.as_str().chars().next());
+        self.l = l;
+        self.c = c;
+        Some(v)
     }
 }
 
 pub fn index<'a>(it: &PIter<'a>) -> ParseRes<'a, Option<usize>> {
-    return Ok((it.clone(), it.index(), None));
+    Ok((*it, it.index(), None))
 }
 
 pub fn line_col<'a>(it: &PIter<'a>) -> ParseRes<'a, (usize, usize)> {
-    return Ok((it.clone(), (it.l, it.c), None));
+    Ok((*it, (it.l, it.c), None))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_col_modes() {
+        let s = "a\u{1F600}e\u{301}\n\u{1F600}z";
+        let p = last(("a", Any.except("z").star()), (line_col, 'z'));
+        let lc = |m| p.parse(&PIter::with_cols(s, m)).map(|(_, (lc, _), _)| lc);
+        assert_eq!(lc(ColMode::Chars), Ok((1, 1)));
+        assert_eq!(lc(ColMode::Utf16), Ok((1, 2)));
+        assert_eq!(lc(ColMode::Width), Ok((1, 2)));
+        let e = 'q'.parse(&PIter::with_cols("\u{1F600}e\u{301}", ColMode::Utf16));
+        assert_eq!(e.unwrap_err().col, 0);
+        let e = ("\u{1F600}e\u{301}", 'q')
+            .parse(&PIter::with_cols("\u{1F600}e\u{301}", ColMode::Utf16));
+        assert_eq!(e.unwrap_err().col, 4);
+    }
+
+    #[test]
+    fn test_tabs_and_line_ends() {
+        let s = "a\r\n\tb\rc\r\rd";
+        let p = star(last(Any.except("bcd").star(), (line_col, Alpha.one())));
+        let lcs = |conf| {
+            p.parse(&PIter::with_conf(s, conf))
+                .map(|(_, v, _)| v.into_iter().map(|(lc, _)| lc).collect::<Vec<_>>())
+        };
+        assert_eq!(lcs(LcConf::new()), Ok(vec![(1, 1), (1, 3), (1, 6)]));
+        let conf = LcConf::new().tab_width(8).line_ends(LineEnds::CrLf);
+        assert_eq!(lcs(conf), Ok(vec![(1, 8), (1, 10), (1, 13)]));
+        let conf = conf.line_ends(LineEnds::All);
+        assert_eq!(lcs(conf), Ok(vec![(1, 8), (2, 0), (4, 0)]));
+        // the field can be set directly, so a zero width is read as one
+        let conf = LcConf {
+            tab: 0,
+            ..LcConf::new()
+        };
+        assert_eq!(lcs(conf), Ok(vec![(1, 1), (1, 3), (1, 6)]));
+        assert_eq!(conf.step((0, 3), '\t', None), (0, 4));
+    }
+
+    #[test]
+    fn test_zero_width_progress() {
+        let s = "e\u{301}\u{301}x";
+        let it = PIter::with_cols(s, ColMode::Width);
+        let (nit, v, _) = star(Any.except("x").one()).parse(&it).unwrap();
+        assert_eq!(v.len(), 3);
+        assert_eq!(nit.lc(), (0, 1));
+        assert_eq!(PIter::at_conf(s, 3, nit.conf()).lc(), (0, 1));
+    }
 }
@@ -0,0 +1,243 @@
+//! Building a tokenizer from a list of rules.
+//!
+//! Each rule is a token kind and a [Parser] for it. Skip rules, for whitespace and comments,
+//! are tried before each token and their text is dropped. The [Token]s produced can be parsed
+//! with the combinators in [crate::slice].
+//!
+//! ```rust
+//! use bogobble::*;
+//! use bogobble::slice::token::Token;
+//!
+//! let lx = Lexer::new()
+//!     .rule("kw", or(keyword("if"), keyword("else")))
+//!     .rule("id", common::Ident)
+//!     .rule("num", NumDigit.plus())
+//!     .rule("op", or!("==", "=", "{", "}"))
+//!     .skip(WSL.plus())
+//!     .skip(("//", Any.except("\n").star()));
+//!
+//! let toks = lx.lex("if x == 10 // check\n{ y = x }").unwrap();
+//! let kinds: Vec<_> = toks.iter().map(|t| t.kind).collect();
+//! assert_eq!(kinds, vec!["kw", "id", "op", "num", "op", "id", "op", "id", "op"]);
+//! assert_eq!(toks[5], Token::new("id", "y", 22, 1, 2));
+//!
+//! let e = lx.lex("x = 4 $").unwrap_err();
+//! assert_eq!((e.index, e.col), (Some(6), 6));
+//! ```
+use crate::err::*;
+use crate::iter::*;
+use crate::parser::*;
+use crate::slice::token::Token;
+
+/// Runs a parser ignoring its result, so rules with different outputs can share a list
+trait Matcher {
+    fn run<'a>(&self, it: &PIter<'a>) -> Result<PIter<'a>, PErr<'a>>;
+}
+
+impl<P: for<'a> Parser<'a>> Matcher for P {
+    fn run<'a>(&self, it: &PIter<'a>) -> Result<PIter<'a>, PErr<'a>> {
+        self.parse(it).map(|(r, _, _)| r)
+    }
+}
+
+/// How to choose between rules that match at the same point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LexMode {
+    /// The rule matching the most text, the earliest on a tie
+    #[default]
+    Longest,
+    /// The first rule to match
+    Priority,
+}
+
+/// A tokenizer built from ordered rules
+pub struct Lexer<K> {
+    rules: Vec<(K, Box<dyn Matcher>)>,
+    skips: Vec<Box<dyn Matcher>>,
+    mode: LexMode,
+}
+
+impl<K> Default for Lexer<K> {
+    fn default() -> Self {
+        Lexer {
+            rules: Vec::new(),
+            skips: Vec::new(),
+            mode: LexMode::Longest,
+        }
+    }
+}
+
+impl<K: Copy> Lexer<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rule<P: for<'a> Parser<'a> + 'static>(mut self, k: K, p: P) -> Self {
+        self.rules.push((k, Box::new(p)));
+        self
+    }
+
+    /// Text matched by a skip rule between tokens is dropped
+    pub fn skip<P: for<'a> Parser<'a> + 'static>(mut self, p: P) -> Self {
+        self.skips.push(Box::new(p));
+        self
+    }
+
+    pub fn mode(mut self, mode: LexMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Use the first rule that matches instead of the longest match
+    pub fn priority(self) -> Self {
+        self.mode(LexMode::Priority)
+    }
+
+    pub fn tokens<'l, 'a>(&'l self, s: &'a str) -> Tokens<'l, 'a, K> {
+        self.tokens_from(&PIter::new(s))
+    }
+
+    /// Tokens from an iterator, so its line settings and file are used
+    pub fn tokens_from<'l, 'a>(&'l self, it: &PIter<'a>) -> Tokens<'l, 'a, K> {
+        Tokens {
+            lx: self,
+            it: *it,
+            done: false,
+        }
+    }
+
+    /// All the tokens in the string, or the first failure
+    pub fn lex<'a>(&self, s: &'a str) -> Result<Vec<Token<'a, K>>, PErr<'a>> {
+        self.tokens(s).collect()
+    }
+
+    fn skip_all<'a>(&self, it: &PIter<'a>) -> PIter<'a> {
+        let mut it = *it;
+        'outer: loop {
+            for s in &self.skips {
+                if let Ok(r) = s.run(&it) {
+                    if r.index() != it.index() {
+                        it = r;
+                        continue 'outer;
+                    }
+                }
+            }
+            return it;
+        }
+    }
+
+    fn next_token<'a>(&self, it: &PIter<'a>) -> Result<(PIter<'a>, K), PErr<'a>> {
+        let mut best: Option<(PIter<'a>, K)> = None;
+        let mut err: Option<PErr<'a>> = None;
+        let start = it.index();
+        for (k, m) in &self.rules {
+            match m.run(it) {
+                Ok(r) if r.index() == start => {}
+                Ok(r) => {
+                    let better = match &best {
+                        None => true,
+                        Some((b, _)) => {
+                            let len = |i: &PIter| i.index().unwrap_or(usize::MAX);
+                            len(&r) > len(b)
+                        }
+                    };
+                    if better {
+                        best = Some((r, *k));
+                    }
+                    if self.mode == LexMode::Priority {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    err = Some(match err {
+                        Some(e2) => e2.longer(e),
+                        None => e,
+                    })
+                }
+            }
+        }
+        match (best, err) {
+            (Some(b), _) => Ok(b),
+            (None, Some(e)) => Err(e.join(it.err_s("a token"))),
+            (None, None) => it.err_rs("a token"),
+        }
+    }
+}
+
+/// The iterator of tokens from [Lexer::tokens], it stops after the first error
+pub struct Tokens<'l, 'a, K> {
+    lx: &'l Lexer<K>,
+    it: PIter<'a>,
+    done: bool,
+}
+
+impl<'l, 'a, K: Copy> Iterator for Tokens<'l, 'a, K> {
+    type Item = Result<Token<'a, K>, PErr<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let it = self.lx.skip_all(&self.it);
+        if it.eoi() {
+            self.done = true;
+            return None;
+        }
+        match self.lx.next_token(&it) {
+            Ok((r, k)) => {
+                let (line, col) = it.lc();
+                let start = it.index().unwrap_or(0);
+                self.it = r;
+                let tok = Token::new(k, it.str_to(r.index()), start, line, col);
+                Some(Ok(tok.with_end_lc(r.lc())))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_longest_and_priority() {
+        let lx = Lexer::new()
+            .rule('k', keyword("in"))
+            .rule('i', Alpha.plus())
+            .rule('o', "<")
+            .rule('o', "<=")
+            .skip(WS.plus());
+        let kinds = |l: &Lexer<char>, s| {
+            l.tokens(s)
+                .map(|t| t.map(|t| (t.kind, t.text)))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        assert_eq!(
+            kinds(&lx, "in inner <="),
+            Ok(vec![('k', "in"), ('i', "inner"), ('o', "<=")])
+        );
+        // "<" comes first so "<=" is never reached
+        let lx = lx.priority();
+        assert_eq!(kinds(&lx, "in <"), Ok(vec![('k', "in"), ('o', "<")]));
+        let e = kinds(&lx, "in inner <=").unwrap_err();
+        assert_eq!(e.index, Some(10));
+    }
+
+    #[test]
+    fn test_token_ends_use_the_conf() {
+        use crate::slice::{token::kind, SParser};
+        let lx = Lexer::new().rule('s', "a\tb").skip("\r\n");
+        let conf = LcConf::new().tab_width(4).line_ends(LineEnds::CrLf);
+        let toks = lx
+            .tokens_from(&PIter::with_conf("\r\na\tb", conf))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!((toks[0].end_line, toks[0].end_col), (1, 5));
+        // an error at the end of the tokens is where the lexer finished
+        let e = (kind('s'), kind('s')).parse_s(&toks).unwrap_err();
+        assert_eq!((e.index, e.line, e.col), (None, 1, 5));
+    }
+}
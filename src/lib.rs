@@ -1,35 +1,70 @@
 #[macro_use]
 pub mod macros;
-//Keep at top
-pub use macros::*;
 
 pub mod charbool;
 pub mod combi;
 pub mod common;
 pub mod convert;
+pub mod dynamic;
 pub mod err;
+#[doc(hidden)]
+pub mod hooks;
+pub mod indent;
+pub mod items;
 pub mod iter;
+pub mod lexer;
+pub mod lines;
+pub mod memo;
 pub mod parser;
 pub mod partial;
+pub mod pratt;
+#[cfg(feature = "trace")]
+pub mod profile;
 pub mod reader;
+pub mod recover;
+pub mod recursive;
 pub mod repeater;
 pub mod select;
+pub mod slice;
+pub mod source;
+pub mod span;
+pub mod state;
+pub mod stream;
 pub mod strings;
 pub mod strung;
+#[cfg(feature = "trace")]
+pub mod trace;
 pub mod traits;
 pub mod tuple;
 
 pub use charbool::*;
 pub use combi::*;
 pub use convert::*;
+pub use dynamic::*;
 pub use err::*;
+pub use indent::*;
+pub use items::*;
 pub use iter::*;
+pub use lexer::*;
+pub use lines::*;
+pub use memo::*;
 pub use parser::*;
+pub use pratt::*;
+#[cfg(feature = "trace")]
+pub use profile::*;
 pub use reader::*;
+pub use recover::*;
+pub use recursive::*;
 pub use repeater::*;
 pub use select::*;
+pub use source::*;
+pub use span::*;
+pub use state::*;
+pub use stream::*;
 pub use strings::*;
 pub use strung::*;
+#[cfg(feature = "trace")]
+pub use trace::*;
 pub use tuple::*;
 
 #[cfg(test)]
@@ -0,0 +1,160 @@
+//! Converting byte offsets to lines and columns after parsing.
+//!
+//! ```rust
+//! use bogobble::*;
+//! let s = "let a = 4;\nlet bc\t= x;";
+//! let conf = LcConf::new().tab_width(4).untracked();
+//! let p = star(wn_(pos_ig((keyword("let"), ws_(Alpha.plus()), ws_('='), ws_(NumDigit.plus()), ';'))));
+//! let (nit, v, _) = p.parse(&PIter::with_conf(s, conf)).unwrap();
+//! // The parse did not count lines
+//! assert_eq!(nit.lc(), (0, 0));
+//!
+//! let li = LineIndex::with_conf(s, conf);
+//! assert_eq!(li.pos_lc(&v[0]), ((0, 0), (0, 10)));
+//! let e = (p, eoi).parse(&PIter::with_conf(s, conf)).unwrap_err();
+//! assert_eq!(li.line_col(e.index.unwrap()), (1, 10));
+//! assert_eq!(li.offset(1, 10), e.index);
+//! ```
+use crate::iter::*;
+use crate::partial::{PosTree, Ranger};
+use crate::reader::Pos;
+use crate::span::Span;
+use std::cell::OnceCell;
+
+/// Maps offsets in a string to (line, col), both counted from 0,
+/// using the same rules as [PIter] with the same [LcConf].
+/// The line starts are only found the first time they are needed.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    s: &'a str,
+    conf: LcConf,
+    starts: OnceCell<Vec<usize>>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self::with_conf(s, LcConf::default())
+    }
+
+    pub fn with_conf(s: &'a str, conf: LcConf) -> Self {
+        LineIndex {
+            s,
+            conf,
+            starts: OnceCell::new(),
+        }
+    }
+
+    fn starts(&self) -> &[usize] {
+        self.starts.get_or_init(|| {
+            let mut res = vec![0];
+            let mut chars = self.s.char_indices().peekable();
+            while let Some((i, c)) = chars.next() {
+                let next = chars.peek().map(|(_, c)| *c);
+                if self.conf.step((0, 0), c, next).0 > 0 {
+                    res.push(i + c.len_utf8());
+                }
+            }
+            res
+        })
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.starts().len()
+    }
+
+    /// The byte offset of the start of the line
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.starts().get(line).copied()
+    }
+
+    /// Offsets past the end of the string are treated as the end of the string
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.s.len());
+        let starts = self.starts();
+        let line = match starts.binary_search(&offset) {
+            Ok(n) => n,
+            Err(n) => n - 1,
+        };
+        let mut col = 0;
+        let mut chars = self.s[starts[line]..].char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if starts[line] + i >= offset {
+                break;
+            }
+            let next = chars.peek().map(|(_, c)| *c);
+            col = self.conf.step((line, col), c, next).1;
+        }
+        (line, col)
+    }
+
+    /// The offset of the char at (line, col), None if there is no such line,
+    /// or the line is not that long
+    pub fn offset(&self, line: usize, col: usize) -> Option<usize> {
+        let start = self.line_start(line)?;
+        let fin = self.line_start(line + 1).unwrap_or(self.s.len());
+        let mut c = 0;
+        let mut chars = self.s[start..].char_indices().peekable();
+        while let Some((i, ch)) = chars.next() {
+            if c >= col || start + i >= fin {
+                return match c == col {
+                    true => Some(start + i),
+                    false => None,
+                };
+            }
+            let next = chars.peek().map(|(_, c)| *c);
+            c = self.conf.step((line, c), ch, next).1;
+        }
+        match c == col {
+            true => Some(self.s.len()),
+            false => None,
+        }
+    }
+
+    /// The start and end (line, col) of a range, open ranges finish at the end of the string
+    pub fn range_lc(&self, r: &Ranger) -> ((usize, usize), (usize, usize)) {
+        match r {
+            Ranger::InEx(a, b) => (self.line_col(*a), self.line_col(*b)),
+            Ranger::InOpen(a) => (self.line_col(*a), self.line_col(self.s.len())),
+        }
+    }
+
+    pub fn span_lc(&self, sp: &Span) -> ((usize, usize), (usize, usize)) {
+        (self.line_col(sp.start), self.line_col(sp.end))
+    }
+
+    pub fn pos_lc<O>(&self, p: &Pos<O>) -> ((usize, usize), (usize, usize)) {
+        self.span_lc(&p.span)
+    }
+
+    pub fn tree_lc<I>(&self, t: &PosTree<I>) -> ((usize, usize), (usize, usize)) {
+        self.span_lc(&t.span)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_line_index_matches_piter() {
+        let s = "ab\r\n\tc\rd\u{1F600}e\n\nf";
+        for conf in [
+            LcConf::new(),
+            LcConf::new().tab_width(4).line_ends(LineEnds::All),
+            LcConf::new().cols(ColMode::Utf16).line_ends(LineEnds::CrLf),
+        ] {
+            let li = LineIndex::with_conf(s, conf);
+            let mut it = PIter::with_conf(s, conf);
+            loop {
+                let i = it.index().unwrap_or(s.len());
+                assert_eq!(li.line_col(i), it.lc(), "at {} with {:?}", i, conf);
+                let lc = it.lc();
+                let o = li.offset(lc.0, lc.1).unwrap();
+                assert_eq!(li.line_col(o), lc, "at {} with {:?}", i, conf);
+                if it.next().is_none() {
+                    break;
+                }
+            }
+        }
+    }
+}
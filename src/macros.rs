@@ -6,7 +6,7 @@
 //!
 
 /// Makes zero sized parsers based on the expression given and potentially the return type given.
-
+///
 /// ```rust
 /// use bogobble::*;
 /// parser!{
@@ -34,14 +34,22 @@ macro_rules! parser {
             type Out = $ot;
             ///Parse run the main parser
             fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
-                let name_e = it.err_s($exp);
-                match (&$x).parse(it){
-                    Ok(v)=> Ok(v),
-                    Err(e)=> match (e.index,name_e.index) {
-                        (Some(ei),Some(ii)) if (ii == ei) => Err(it.err_s($exp)),
-                        _=>Err(e.join(name_e)),
+                $crate::hooks::trace_rule($exp, it, || {
+                    let name_e = it.err_s($exp);
+                    match (&$x).parse(it){
+                        Ok(v)=> Ok(v),
+                        Err(e)=> match (e.index,name_e.index) {
+                            (Some(ei),Some(ii)) if (ii == ei) => Err(it.err_s($exp).cover_to(e.end)),
+                            _=>Err(e.join(name_e).in_context($exp)),
+                        }
                     }
-                }
+                })
+            }
+        }
+        impl<'a, S> $crate::state::StParser<'a, S> for $id {
+            type Out = $ot;
+            fn parse_st(&self, it: &PIter<'a>, _: &mut S) -> ParseRes<'a, Self::Out> {
+                self.parse(it)
             }
         }
     };
@@ -104,15 +112,16 @@ macro_rules! char_bools {
 /// ```
 #[macro_export]
 macro_rules! or{
-    ($s:expr,$($x:expr),* $(,)?) => { $s$(.or($x))*;};
+    ($s:expr,$($x:expr),* $(,)?) => { $s$(.or($x))*};
 }
 
 #[macro_export]
 macro_rules! or_ig{
-    ($s:expr,$($x:expr),* $(,)?) => { $s.ig()$(.or($x.ig()))*;};
+    ($s:expr,$($x:expr),* $(,)?) => { $s.ig()$(.or($x.ig()))*};
 }
 
 #[cfg(test)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
 mod test {
     fn size_of<T: Sized>(_t: &T) -> usize {
         std::mem::size_of::<T>()
@@ -135,8 +144,35 @@ mod test {
         );
     }
 
+    parser!((PAIR->(&'static str, &'static str)) (CAT, DOG));
+    parser!((NEST->(&'static str, (&'static str, &'static str))) ("<", PAIR));
+
+    #[test]
+    pub fn parser_errors_have_context() {
+        let e = NEST.parse_s("<catcar").unwrap_err();
+        assert_eq!(e.exp, err::Expected::Str("DOG"));
+        assert_eq!(e.context(), ["NEST", "PAIR"]);
+        let e = NEST.parse_s("<car").unwrap_err();
+        assert_eq!(e.context(), ["NEST"]);
+        assert!(e.strung().to_string().starts_with("while parsing NEST: "));
+        // every parser returns a PErr, so it stays under clippy's result_large_err size
+        assert!(std::mem::size_of::<err::PErr>() < 128);
+    }
+
+    parser!((IFX->String) (keyword("if"), Cut, ws_(common::Ident)).map(|(_, _, i)| i));
+
+    #[test]
+    pub fn cut_is_scoped_to_rule() {
+        let e = (IFX, ws_(';')).parse_s("if x").unwrap_err();
+        assert!(!e.is_break);
+        let e = or(IFX, common::Ident).parse_s("if 3").unwrap_err();
+        assert!(e.is_break);
+        let p = or(attempt(IFX), common::Ident);
+        assert_eq!(p.parse_s("if 3"), Ok("if".to_string()));
+    }
+
     char_bool!(HOT, "hot");
-    char_bool!(MNUM, |c| c >= '0' && c <= '9');
+    char_bool!(MNUM, |c: char| c.is_ascii_digit());
 
     #[test]
     pub fn charbool_macro_makes_parser() {
@@ -0,0 +1,158 @@
+//! Packrat style memoization.
+//!
+//! [memo] wraps a parser so that each result, including failures, is stored by the offset it
+//! started at. When backtracking reaches the same rule at the same offset again, the stored
+//! result is returned instead of parsing again. The cache belongs to the [Memo], it is
+//! cleared automatically when a different input is seen, or by calling [Memo::clear].
+//! Results are also keyed by the iterator's [LcConf], file and [PIter::indent], so one Memo
+//! can be used with iterators that count or lay out the same text differently.
+//!
+//! ```rust
+//! use bogobble::*;
+//! let num = memo(ws_(common::Int));
+//! // each alternative starts with the same number, which is only parsed once
+//! let p = or!(
+//!     (num.br(), ws_('+'), num.br()).map(|(a, _, b)| a + b),
+//!     (num.br(), ws_('-'), num.br()).map(|(a, _, b)| a - b),
+//!     num.br(),
+//! );
+//! assert_eq!(p.parse_s("40 - 2"), Ok(38));
+//! assert_eq!(num.stats(), MemoStats { hits: 1, misses: 2 });
+//!
+//! // a new input starts with an empty cache
+//! assert_eq!(p.parse_s("7"), Ok(7));
+//! assert_eq!(num.stats(), MemoStats { hits: 3, misses: 3 });
+//! assert_eq!(num.stats().hit_rate(), 0.5);
+//! ```
+use crate::iter::*;
+use crate::parser::*;
+use crate::source::FileId;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+/// Everything about the iterator, besides the input, that a result can depend on
+type Key = (Option<usize>, LcConf, Option<FileId>, usize);
+
+fn key(it: &PIter) -> Key {
+    (it.index(), it.conf(), it.file(), it.indent())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl MemoStats {
+    /// The share of lookups found in the cache, 0 if there have been none
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.,
+            n => self.hits as f64 / n as f64,
+        }
+    }
+}
+
+/// A parser that caches its results by where they started, see [memo]
+pub struct Memo<'a, P: Parser<'a>> {
+    p: P,
+    /// The address and length of the input the cache is for
+    input: Cell<(usize, usize)>,
+    cache: RefCell<HashMap<Key, ParseRes<'a, P::Out>>>,
+    stats: Cell<MemoStats>,
+}
+
+pub fn memo<'a, P: Parser<'a>>(p: P) -> Memo<'a, P> {
+    Memo {
+        p,
+        input: Cell::new((0, 0)),
+        cache: RefCell::new(HashMap::new()),
+        stats: Cell::new(MemoStats::default()),
+    }
+}
+
+impl<'a, P: Parser<'a>> Memo<'a, P> {
+    /// Empties the cache, the stats are kept
+    pub fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats.get()
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.set(MemoStats::default());
+    }
+
+    /// The number of results currently stored
+    pub fn len(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.borrow().is_empty()
+    }
+}
+
+impl<'a, P: Parser<'a>> Parser<'a> for Memo<'a, P>
+where
+    P::Out: Clone,
+{
+    type Out = P::Out;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, P::Out> {
+        let s = it.orig_str();
+        let input = (s.as_ptr() as usize, s.len());
+        if self.input.get() != input {
+            self.clear();
+            self.input.set(input);
+        }
+        let k = key(it);
+        let mut st = self.stats.get();
+        if let Some(r) = self.cache.borrow().get(&k) {
+            st.hits += 1;
+            self.stats.set(st);
+            return r.clone();
+        }
+        st.misses += 1;
+        self.stats.set(st);
+        // The cache is not borrowed while parsing, so p may use this parser again
+        let r = self.p.parse(it);
+        self.cache.borrow_mut().insert(k, r.clone());
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_memo_caches_failures() {
+        let m = memo(('a', 'b'));
+        let p = or((m.br(), 'c').ig(), m.br().ig());
+        assert!(p.parse_s("ax").is_err());
+        assert_eq!(m.stats(), MemoStats { hits: 1, misses: 1 });
+        assert!(m.parse_s("ab").is_ok());
+        assert_eq!(m.stats().misses, 2);
+        assert_eq!(m.len(), 1);
+        m.clear();
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_memo_keys_on_conf() {
+        let s = "日本x";
+        let m = memo(last(Any.except("x").star(), line_col));
+        let at = |it: PIter<'static>| m.parse(&it).map(|(_, lc, _)| lc);
+        assert_eq!(at(PIter::new(s)), Ok((0, 2)));
+        assert_eq!(at(PIter::with_cols(s, ColMode::Width)), Ok((0, 4)));
+        assert_eq!(at(PIter::new(s)), Ok((0, 2)));
+        assert_eq!(m.stats(), MemoStats { hits: 1, misses: 2 });
+        // a block inside a deeper one needs more indentation
+        let b = memo(indented_block((Alpha.plus(), ';')));
+        let it = PIter::at("x:\n  a;", 2);
+        assert!(b.parse(&it).is_ok());
+        assert!(b.parse(&it.with_indent(4)).is_err());
+    }
+}
@@ -1,10 +1,25 @@
+use crate::combi::{context, Context};
 use crate::convert::*;
+use crate::dynamic::BoxParser;
 use crate::err::*;
+use crate::items::ParseIter;
 use crate::iter::*;
 use crate::select::*;
+use crate::span::*;
 
 pub type ParseRes<'a, V> = Result<(PIter<'a>, V, Option<PErr<'a>>), PErr<'a>>;
 
+/// A successful parse of the whole input, from [Parser::parse_all] or [Parser::parse_full]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parsed<'a, V> {
+    pub val: V,
+    /// Errors that did not stop the parse, moved out of a [crate::Recovered] value by
+    /// [Parsed::take_recovered]
+    pub warnings: Vec<PErr<'a>>,
+    /// The number of bytes parsed, from where the iterator started to the end of the input
+    pub consumed: usize,
+}
+
 pub trait ErrJoin<'a>: Sized {
     fn join_err(self, e: PErr<'a>) -> Self;
     fn join_err_op(self, e: Option<PErr<'a>>) -> Self {
@@ -71,16 +86,68 @@ pub trait Parser<'a>: Sized {
     type Out;
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out>;
 
+    /// True if failures after this parser in a tuple should be breaks, see [Cut]
+    fn is_cut(&self) -> bool {
+        false
+    }
+
     fn parse_s(&self, s: &'a str) -> Result<Self::Out, PErr<'a>> {
         self.parse(&PIter::new(s)).map(|(_, v, _)| v)
     }
 
+    /// Parses the whole string, trailing input is an error, as with `(p, eoi)`.
+    /// Errors from the recovery combinators become warnings with [Parsed::take_recovered].
+    ///
+    /// ```rust
+    /// use bogobble::*;
+    /// assert_eq!(common::Int.parse_s("32abc"), Ok(32));
+    /// assert_eq!(common::Int.parse_all("32abc").unwrap_err().index, Some(2));
+    ///
+    /// let list = sep_plus(common::Int, ',');
+    /// let e = list.parse_all("1,2;3").unwrap_err();
+    /// assert_eq!(e.exp, Expected::OneOf(vec![Expected::EOI, Expected::Char(',')]));
+    ///
+    /// let r = list.parse_all("1,-32").unwrap();
+    /// assert_eq!((r.val, r.consumed), (vec![1, -32], 5));
+    ///
+    /// let list = sep_star_recover(common::Int, ',', skip_until(','));
+    /// let r = list.parse_all("1,x,3").unwrap().take_recovered();
+    /// assert_eq!((r.val, r.warnings.len()), (vec![1, 3], 1));
+    /// assert_eq!(r.warnings[0].index, Some(2));
+    /// ```
+    fn parse_all(&self, s: &'a str) -> Result<Parsed<'a, Self::Out>, PErr<'a>> {
+        self.parse_full(&PIter::new(s))
+    }
+
+    /// As [Parser::parse_all], from an iterator, so its position, file and line settings are
+    /// used
+    fn parse_full(&self, it: &PIter<'a>) -> Result<Parsed<'a, Self::Out>, PErr<'a>> {
+        let (r, val, ex) = self.parse(it)?;
+        if !r.eoi() {
+            let e = r.err(Expected::EOI);
+            return Err(match ex {
+                Some(ex) => e.join(ex),
+                None => e,
+            });
+        }
+        Ok(Parsed {
+            val,
+            warnings: Vec::new(),
+            consumed: it.as_str().len(),
+        })
+    }
+
+    /// Lazily parses one item after another until the end of the string, see [crate::items]
+    fn parse_iter(&self, s: &'a str) -> ParseIter<'_, 'a, Self> {
+        ParseIter::new(self, &PIter::new(s))
+    }
+
     fn or<B: Parser<'a, Out = Self::Out>>(self, b: B) -> Or<Self, B> {
         or(self, b)
     }
 
     ///Borrowed Parser
-    fn br(&self) -> BRP<Self> {
+    fn br(&self) -> BRP<'_, Self> {
         BRP(self)
     }
 
@@ -95,13 +162,51 @@ pub trait Parser<'a>: Sized {
     fn try_map<B, F: Fn(Self::Out) -> Result<B, Expected>>(self, f: F) -> TryMap<Self, F> {
         try_map(self, f)
     }
+    /// Fail unless f accepts the result, with an error message built from it
+    fn verify<F, M>(self, f: F, m: M) -> Verify<Self, F, M>
+    where
+        F: Fn(&Self::Out) -> bool,
+        M: Fn(&Self::Out) -> String,
+    {
+        verify(self, f, m)
+    }
+
+    /// The result with the [Span] of input it came from
+    fn spanned(self) -> Spanned<Self> {
+        spanned(self)
+    }
+
+    /// As [Parser::map], f is also given the span and the text matched
+    fn map_with_span<B, F: Fn(Self::Out, Span, &'a str) -> B>(self, f: F) -> MapWithSpan<Self, F> {
+        map_with_span(self, f)
+    }
+
     fn ig(self) -> Ig<Self> {
         Ig { a: self }
     }
 
+    /// Parse with a parser built from this one's result
+    fn and_then<B: Parser<'a>, F: Fn(Self::Out) -> B>(self, f: F) -> AndThen<Self, F> {
+        and_then(self, f)
+    }
+
+    /// Failures become breaks, which stop [crate::or] trying the next branch
     fn brk(self) -> Break<Self> {
         Break { a: self }
     }
+
+    /// Label errors from inside this parser, see [crate::PErr::context]
+    fn context(self, s: &'static str) -> Context<Self> {
+        context(self, s)
+    }
+
+    /// Box the parser as a trait object, to store it without naming its type
+    fn boxed(self) -> BoxParser<'a, Self::Out>
+    where
+        Self: 'a,
+    {
+        Box::new(self)
+    }
 }
 
 impl<'a, F, V> Parser<'a> for F
@@ -117,11 +222,11 @@ where
 impl<'a> Parser<'a> for &'static str {
     type Out = &'static str;
     fn parse(&self, i: &PIter<'a>) -> ParseRes<'a, Self::Out> {
-        let mut it = i.clone();
+        let mut it = *i;
         for c in self.chars() {
             match it.next() {
                 Some(ic) if ic == c => {}
-                _ => return Err(i.err_s(self)),
+                _ => return Err(i.err_s(self).cover_to(it.index())),
             }
         }
         Ok((it, self, None))
@@ -132,10 +237,10 @@ impl<'a> Parser<'a> for char {
     type Out = char;
 
     fn parse(&self, i: &PIter<'a>) -> ParseRes<'a, Self::Out> {
-        let mut it = i.clone();
+        let mut it = *i;
         match it.next() {
             Some(ic) if ic == *self => Ok((it, ic, None)),
-            _ => return Err(i.err(Expected::Char(*self))),
+            _ => Err(i.err(Expected::Char(*self))),
         }
     }
 }
@@ -147,10 +252,13 @@ impl<'a, 'b, P: Parser<'a>> Parser<'a> for BRP<'b, P> {
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
         self.0.parse(it)
     }
+    fn is_cut(&self) -> bool {
+        self.0.is_cut()
+    }
 }
 
 pub struct Break<A> {
-    a: A,
+    pub(crate) a: A,
 }
 
 impl<'a, A: Parser<'a>> Parser<'a> for Break<A> {
@@ -159,3 +267,64 @@ impl<'a, A: Parser<'a>> Parser<'a> for Break<A> {
         self.a.parse(it).map_err(|e| e.brk())
     }
 }
+
+/// Commits a tuple to its current branch. Once the elements before it have matched, any
+/// failure after it in the same tuple is a break, so [crate::or], [crate::maybe],
+/// [crate::exists] and the repeaters do not try anything else. A plain [Parser::brk] only stops [crate::or], maybe
+/// and the repeaters still treat it as a normal failure. The cut covers the rest of the tuple it is in,
+/// including outer tuples, but not past a rule made by [parser!] or any other wrapper.
+///
+/// ```rust
+/// use bogobble::*;
+/// let stmt = or(
+///     (keyword("if"), Cut, ws_(common::Ident), ws_(';')).map(|(_, _, c, _)| c),
+///     (ws_(common::Ident), ws_(';')).map(|(c, _)| c),
+/// );
+/// assert_eq!(stmt.parse_s("if x;"), Ok("x".to_string()));
+/// // "if" could be an identifier, but the cut stops the second branch being tried
+/// let e = stmt.parse_s("if;").unwrap_err();
+/// assert!(e.is_break);
+/// assert_eq!(e.index, Some(2));
+///
+/// // The break is not swallowed by star, so the error is where the problem is
+/// let prog = (star(stmt.br()), ws_(eoi));
+/// assert_eq!(prog.parse_s("if x; y; if 3;").unwrap_err().index, Some(12));
+/// // attempt stops the cut at its edge
+/// assert_eq!(maybe(attempt(stmt.br())).parse_s("if;"), Ok(None));
+/// assert!(exists(stmt.br()).parse_s("if 3").unwrap_err().is_break);
+/// // while a plain break is still hidden by maybe
+/// assert!((maybe(('a', 'b').brk()), 'a').parse_s("ac").is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cut;
+
+impl<'a> Parser<'a> for Cut {
+    type Out = ();
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, ()> {
+        Ok((*it, (), None))
+    }
+    fn is_cut(&self) -> bool {
+        true
+    }
+}
+
+pub struct Attempt<A> {
+    pub(crate) a: A,
+}
+
+/// Turns breaks from inside p back into normal failures,
+/// so a [Cut] or [Parser::brk] only commits within p
+pub fn attempt<'a, A: Parser<'a>>(a: A) -> Attempt<A> {
+    Attempt { a }
+}
+
+impl<'a, A: Parser<'a>> Parser<'a> for Attempt<A> {
+    type Out = A::Out;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, A::Out> {
+        self.a.parse(it).map_err(|mut e| {
+            e.is_break = false;
+            e.is_cut = false;
+            e
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use super::PosTree;
+use crate::span::Span;
 use crate::traits::*;
 
 pub struct S(pub &'static str);
@@ -6,7 +7,7 @@ pub struct S(pub &'static str);
 impl<'a> Parser<'a> for S {
     type Out = &'static str;
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
-        let mut i2 = it.clone();
+        let mut i2 = *it;
         for c in self.0.chars() {
             match i2.next() {
                 None => return Ok((i2, self.0, None)),
@@ -39,14 +40,17 @@ pub struct PCUntil<C, P, I> {
 impl<'a, C: CharBool, P: Parser<'a, Out = PosTree<I>>, I: Clone> Parser<'a> for PCUntil<C, P, I> {
     type Out = (PosTree<I>, P::Out);
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
-        let mut i2 = it.clone();
+        let mut i2 = *it;
         loop {
             let p_err = match self.p.parse(&i2) {
                 Ok((i3, r2, e_op)) => {
-                    let fin = i3.index();
                     return Ok((
                         i3,
-                        (PosTree::new(it.index(), fin, self.i.clone()), r2),
+                        (
+                            PosTree::new(Span::new(it.offset(), i3.offset()), self.i.clone())
+                                .in_file(it.file()),
+                            r2,
+                        ),
                         e_op,
                     ));
                 }
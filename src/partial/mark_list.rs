@@ -18,27 +18,28 @@ impl Display for MarkErr {
 }
 
 pub fn mark_str<I: Clone + Display>(t: &PosTree<I>, s: &str) -> Result<String, MarkErr> {
-    MarkList::new(t, None).mark_str(s)
+    MarkList::new(t, None, s.len()).mark_str(s)
 }
 
 struct MarkList<I>(BTreeMap<usize, I>);
 
 impl<I: Clone> MarkList<I> {
-    pub fn new(t: &PosTree<I>, end: Option<&I>) -> Self {
+    pub fn new(t: &PosTree<I>, end: Option<&I>, len: usize) -> Self {
         let mut res = MarkList(BTreeMap::new());
-        res.set_marks(t, end);
+        res.set_marks(t, end, len);
         res
     }
 
-    pub fn set_marks(&mut self, t: &PosTree<I>, end: Option<&I>) {
-        if let Some(s) = t.start {
-            self.0.insert(s, t.item.clone());
+    /// Nothing is marked at the end of the input, where incomplete trees finish
+    pub fn set_marks(&mut self, t: &PosTree<I>, end: Option<&I>, len: usize) {
+        if t.span.start < len {
+            self.0.insert(t.span.start, t.item.clone());
         }
         for c in &t.children {
-            self.set_marks(c, Some(&t.item));
+            self.set_marks(c, Some(&t.item), len);
         }
-        if let (Some(s), Some(e)) = (t.fin, end) {
-            self.0.insert(s, e.clone());
+        if let (Some(e), true) = (end, t.span.end < len) {
+            self.0.insert(t.span.end, e.clone());
         }
     }
 
@@ -67,3 +68,22 @@ impl<I: Clone + Display> MarkList<I> {
         self.mark_str_with(s, |i| i.to_string())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::partial::*;
+    use crate::*;
+
+    #[test]
+    fn test_no_marks_at_the_end() {
+        assert_eq!(
+            mark_str(&tpos(eoi, "E").parse_s("").unwrap(), ""),
+            Ok("".to_string())
+        );
+        // a child's end is marked with its parent, but not where the input finishes
+        let p = vpos(star(or(tpos("ab", "A"), tpos("cd", "C"))), "T");
+        let t = p.parse_s("abcd").unwrap();
+        assert_eq!(mark_str(&t, "abcd"), Ok("AabCcd".to_string()));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::source::FileId;
+use crate::span::Span;
 use crate::traits::*;
 use crate::EOI;
 pub mod charbool;
@@ -5,7 +7,6 @@ pub mod ranger;
 pub use traits::*;
 pub mod mark_list;
 pub mod p_repeat;
-use ranger::Ranger;
 pub mod traits {
     pub use super::charbool::PartCharBool;
     pub use super::ranger::*;
@@ -16,53 +17,57 @@ pub use p_repeat::*;
 
 #[derive(Debug, Clone)]
 pub struct PosTree<I> {
-    pub start: Option<usize>,
-    pub fin: Option<usize>,
+    pub span: Span,
     pub complete: bool,
     pub item: I,
     pub children: Vec<PosTree<I>>,
+    pub file: Option<FileId>,
 }
 
 impl<I> PosTree<I> {
-    pub fn new(start: Option<usize>, fin: Option<usize>, item: I) -> Self {
+    pub fn new(span: Span, item: I) -> Self {
         PosTree {
-            start,
-            fin,
+            span,
             item,
             complete: true,
             children: Vec::new(),
+            file: None,
         }
     }
 
+    pub fn in_file(mut self, file: Option<FileId>) -> Self {
+        self.file = file;
+        self
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.fin == self.start
+        self.span.is_empty()
     }
 
     fn merge(self, item: I, b: Self) -> Self {
-        let start = self.start;
-        let fin = b.fin;
+        let span = Span::new(self.span.start, b.span.end);
         let complete = self.complete && b.complete;
+        let file = self.file;
         let children = match b.is_empty() {
             true => vec![self],
             false => vec![self, b],
         };
         PosTree {
-            start,
-            fin,
+            span,
             complete,
             item,
             children,
+            file,
         }
     }
 
-    fn as_child(self, item: I) -> Self {
+    fn into_child(self, item: I) -> Self {
         PosTree {
-            start: self.start,
-            fin: self.fin,
+            span: self.span,
             complete: self.complete,
+            file: self.file,
             item,
             children: vec![self],
-            ..self
         }
     }
 
@@ -73,7 +78,7 @@ impl<I> PosTree<I> {
 
     pub fn push(mut self, b: Self) -> Self {
         if !b.is_empty() {
-            self.fin = b.fin;
+            self.span.end = b.span.end;
             self.children.push(b);
         }
         self
@@ -82,11 +87,7 @@ impl<I> PosTree<I> {
     ///Grab str from reference between points
     ///panics if str not long enough
     pub fn on_str<'a>(&self, s: &'a str) -> &'a str {
-        match (self.start, self.fin) {
-            (Some(a), Some(b)) => &s[a..b],
-            (Some(a), _) => &s[a..],
-            _ => "",
-        }
+        &s[self.span.start..self.span.end]
     }
 
     pub fn str_len(&self, s: &str) -> usize {
@@ -95,11 +96,11 @@ impl<I> PosTree<I> {
 
     pub fn find_at_end<F: Fn(&I) -> bool>(&self, s: &str, f: F) -> Option<&Self> {
         if f(&self.item) {
-            return Some(&self);
+            return Some(self);
         }
         let mut res = None;
         for x in &self.children {
-            if x.complete && x.start != None {
+            if x.complete && x.span.start < s.len() {
                 res = Some(x)
             }
         }
@@ -107,12 +108,7 @@ impl<I> PosTree<I> {
     }
 
     pub fn range(&self) -> Ranger {
-        match (&self.start, &self.fin) {
-            (Some(s), Some(e)) => Ranger::InEx(*s, *e),
-            (Some(s), None) => Ranger::InOpen(*s),
-            (None, Some(e)) => Ranger::InEx(0, *e),
-            _ => Ranger::InOpen(0),
-        }
+        Ranger::InEx(self.span.start, self.span.end)
     }
 }
 
@@ -132,8 +128,8 @@ where
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, PosTree<I>> {
         if it.eoi() {
             return Ok((
-                it.clone(),
-                PosTree::new(it.index(), it.index(), self.i.clone()),
+                *it,
+                PosTree::new(Span::at(it.offset()), self.i.clone()).in_file(it.file()),
                 None,
             ));
         }
@@ -142,7 +138,7 @@ where
         match self.b.parse(&i1) {
             Ok((i2, p2, e2)) => Ok((i2, p1.merge(self.i.clone(), p2), e2)),
             Err(e2) => match i1.eoi() {
-                true => Ok((i1, p1.as_child(self.i.clone()).incomplete(), None)),
+                true => Ok((i1, p1.into_child(self.i.clone()).incomplete(), None)),
                 false => Err(e2),
             },
         }
@@ -241,16 +237,14 @@ impl<'a, P: Parser<'a>, I: Clone> Parser<'a> for PosTreeParse<P, I> {
     type Out = PosTree<I>;
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, PosTree<I>> {
         let (i2, _, e) = self.p.parse(it)?;
-        let fin = i2.index();
-        let start = it.index();
         Ok((
             i2,
             PosTree {
-                start,
-                fin,
+                span: Span::new(it.offset(), i2.offset()),
                 item: self.item.clone(),
                 children: Vec::new(),
                 complete: true,
+                file: it.file(),
             },
             e,
         ))
@@ -268,13 +262,13 @@ impl<'a, I: Clone, P: Parser<'a, Out = PosTree<I>>> Parser<'a> for PMaybe<P, I>
         match self.p.parse(it) {
             Ok(v) => Ok(v),
             Err(e) => Ok((
-                it.clone(),
+                *it,
                 PosTree {
-                    start: it.index(),
-                    fin: it.index(),
+                    span: Span::at(it.offset()),
                     item: self.i.clone(),
                     complete: true,
                     children: Vec::new(),
+                    file: it.file(),
                 },
                 Some(e),
             )),
@@ -309,22 +303,22 @@ impl<'a, P: Parser<'a, Out = Vec<PosTree<I>>>, I: Clone> Parser<'a> for PosVecPa
                         .unwrap_or_else(|| EOI.parse(&i2).is_ok()),
                 };
                 let res = PosTree {
-                    start: it.index(),
-                    fin: i2.index(),
+                    span: Span::new(it.offset(), i2.offset()),
                     item: self.i.clone(),
                     complete,
                     children: vc,
+                    file: it.file(),
                 };
                 (i2, res, e)
             })
             .or_else(|e| {
                 EOI.parse(it)
                     .map_v(|_| PosTree {
-                        start: it.index(),
-                        fin: it.index(),
+                        span: Span::at(it.offset()),
                         item: self.i.clone(),
                         complete: false,
                         children: Vec::new(),
+                        file: it.file(),
                     })
                     .map_err(|_| e)
             })
@@ -35,7 +35,7 @@ fn do_sep<'a, A: Parser<'a>, B: Parser<'a>>(
     exact: bool,
 ) -> ParseRes<'a, Vec<A::Out>> {
     let mut res = Vec::new();
-    let mut ri = i.clone();
+    let mut ri = *i;
     loop {
         ri = match a.parse(&ri) {
             Ok((r, v, _)) => {
@@ -46,7 +46,7 @@ fn do_sep<'a, A: Parser<'a>, B: Parser<'a>>(
                 if ri.eoi() {
                     return Ok((ri, res, None));
                 }
-                if res.len() == 0 && min == 0 {
+                if res.is_empty() && min == 0 {
                     return Ok((ri, res, Some(e)));
                 }
                 if res.len() == min && exact {
@@ -119,7 +119,7 @@ pub fn do_rep<'a, A: Parser<'a>>(
     min: usize,
     exact: bool,
 ) -> ParseRes<'a, Vec<A::Out>> {
-    let mut it = i.clone();
+    let mut it = *i;
     let mut res = Vec::new();
 
     loop {
@@ -129,7 +129,7 @@ pub fn do_rep<'a, A: Parser<'a>>(
                 if i2.eoi() {
                     return Ok((i2, res, None));
                 }
-                if it.lc() == i2.lc() && !exact {
+                if it.index() == i2.index() && !exact {
                     return Err(it.err_s("To Consume some data"));
                 }
                 if res.len() == min && exact {
@@ -176,7 +176,7 @@ pub fn p_plus<'a, A: Parser<'a>>(a: A) -> PPlus<A> {
 }
 
 #[cfg(test)]
-pub mod test {
+mod test {
     use super::*;
     //use crate::ptrait::*;
     use crate::*;
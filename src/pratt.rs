@@ -0,0 +1,266 @@
+//! Operator precedence parsing.
+//!
+//! [pratt] takes an atom parser, and is then given operators, each an ordinary [Parser] with
+//! a binding power and a callback to build the result. Higher binding powers bind tighter.
+//! Operators are tried in the order they were added.
+//!
+//! Building the table boxes every operator, so build it once and keep it, rather than inside
+//! a [parser!] rule which would build it again on each call. [crate::recursive] lets the
+//! atoms refer back to the whole expression.
+//!
+//! ```rust
+//! use bogobble::*;
+//! #[derive(Debug, PartialEq)]
+//! pub enum Ex {
+//!     Num(isize),
+//!     Neg(Box<Ex>),
+//!     Op(Box<Ex>, char, Box<Ex>),
+//!     Fact(Box<Ex>),
+//!     If(Box<Ex>, Box<Ex>, Box<Ex>),
+//! }
+//! use Ex::*;
+//!
+//! let expr = recursive(|expr| {
+//!     pratt(or(ws_(common::Int).map(Num), middle(ws_('('), expr, ws_(')'))))
+//!         .prefix(5, ws_('-'), |_, v| Neg(Box::new(v)))
+//!         .infix_left(2, ws_(or('+', '-')), |a, o, b| Op(Box::new(a), o, Box::new(b)))
+//!         .infix_left(3, ws_(or('*', '/')), |a, o, b| Op(Box::new(a), o, Box::new(b)))
+//!         .infix_right(4, ws_('^'), |a, o, b| Op(Box::new(a), o, Box::new(b)))
+//!         .postfix(6, ws_('!'), |v, _| Fact(Box::new(v)))
+//!         .ternary(1, ws_('?'), ws_(':'), |c, a, b| If(Box::new(c), Box::new(a), Box::new(b)))
+//! });
+//!
+//! let n = |n| Box::new(Num(n));
+//! assert_eq!(expr.parse_s("1 - 2 - 3"), Ok(Op(Box::new(Op(n(1), '-', n(2))), '-', n(3))));
+//! assert_eq!(expr.parse_s("2 ^ 3 ^ 4"), Ok(Op(n(2), '^', Box::new(Op(n(3), '^', n(4))))));
+//! assert_eq!(expr.parse_s("-2!"), Ok(Neg(Box::new(Fact(n(2))))));
+//! assert_eq!(expr.parse_s("1 + 2 * 3"), Ok(Op(n(1), '+', Box::new(Op(n(2), '*', n(3))))));
+//! assert_eq!(
+//!     expr.parse_s("1 ? 2 : 3 + 4"),
+//!     Ok(If(n(1), n(2), Box::new(Op(n(3), '+', n(4)))))
+//! );
+//!
+//! let e = expr.parse_s("1 + * 2").unwrap_err();
+//! assert_eq!((e.exp, e.index), (Expected::Str("operand"), Some(4)));
+//! let e = (expr.clone(), eoi).parse_s("(1 + 2 3").unwrap_err();
+//! assert_eq!(e.index, Some(7));
+//! ```
+use crate::err::*;
+use crate::iter::*;
+use crate::parser::*;
+
+type AtomFn<'a, V> = Box<dyn Fn(&PIter<'a>) -> ParseRes<'a, V> + 'a>;
+
+/// The outer error means the operator was not found,
+/// the inner result fails if the operator's operands are missing
+type PrefixFn<'a, V> =
+    Box<dyn Fn(&Pratt<'a, V>, &PIter<'a>) -> Result<ParseRes<'a, V>, PErr<'a>> + 'a>;
+
+/// As [PrefixFn], but gives the left hand side back if the operator was not found
+type PostFn<'a, V> =
+    Box<dyn Fn(&Pratt<'a, V>, &PIter<'a>, V) -> Result<ParseRes<'a, V>, (V, PErr<'a>)> + 'a>;
+
+/// An expression parser, see [pratt]
+pub struct Pratt<'a, V> {
+    atom: AtomFn<'a, V>,
+    prefix: Vec<PrefixFn<'a, V>>,
+    /// infix, postfix and ternary operators with their left binding power
+    post: Vec<(u32, PostFn<'a, V>)>,
+}
+
+/// Builds an expression parser from the atom.
+/// Binding powers are doubled internally to make room for associativity.
+pub fn pratt<'a, A: Parser<'a> + 'a>(atom: A) -> Pratt<'a, A::Out> {
+    Pratt {
+        atom: Box::new(move |it| atom.parse(it)),
+        prefix: Vec::new(),
+        post: Vec::new(),
+    }
+}
+
+/// Errors from operators that fail before anything but whitespace become the expected `s`,
+/// so the message names what was missing rather than listing every operator.
+/// Errors further on are kept.
+fn op_err<'a>(it: &PIter<'a>, mut e: PErr<'a>, s: &'static str) -> PErr<'a> {
+    if it.str_to(e.index).trim().is_empty() {
+        e.exp = Expected::Str(s);
+        e.child = None;
+    }
+    e
+}
+
+fn longer_op<'a>(a: Option<PErr<'a>>, b: PErr<'a>) -> PErr<'a> {
+    match a {
+        Some(a) if a.index == b.index && a.exp == b.exp => a,
+        Some(a) => a.longer(b),
+        None => b,
+    }
+}
+
+impl<'a, V: 'a> Pratt<'a, V> {
+    /// An operator before its operand, the operand binds as tightly as bp
+    pub fn prefix<P, F>(mut self, bp: u32, p: P, f: F) -> Self
+    where
+        P: Parser<'a> + 'a,
+        F: Fn(P::Out, V) -> V + 'a,
+    {
+        self.prefix.push(Box::new(move |pr, it| {
+            let (i2, o, _) = p.parse(it)?;
+            Ok(pr.expr(&i2, bp * 2).map(|(i3, v, ex)| (i3, f(o, v), ex)))
+        }));
+        self
+    }
+
+    fn infix<P, F>(mut self, lbp: u32, rbp: u32, p: P, f: F) -> Self
+    where
+        P: Parser<'a> + 'a,
+        F: Fn(V, P::Out, V) -> V + 'a,
+    {
+        self.post.push((
+            lbp,
+            Box::new(move |pr, it, lhs| match p.parse(it) {
+                Ok((i2, o, _)) => Ok(pr
+                    .expr(&i2, rbp)
+                    .map(|(i3, rhs, ex)| (i3, f(lhs, o, rhs), ex))),
+                Err(e) => Err((lhs, e)),
+            }),
+        ));
+        self
+    }
+
+    /// `a - b - c` is `(a - b) - c`
+    pub fn infix_left<P, F>(self, bp: u32, p: P, f: F) -> Self
+    where
+        P: Parser<'a> + 'a,
+        F: Fn(V, P::Out, V) -> V + 'a,
+    {
+        self.infix(bp * 2, bp * 2 + 1, p, f)
+    }
+
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`
+    pub fn infix_right<P, F>(self, bp: u32, p: P, f: F) -> Self
+    where
+        P: Parser<'a> + 'a,
+        F: Fn(V, P::Out, V) -> V + 'a,
+    {
+        self.infix(bp * 2 + 1, bp * 2, p, f)
+    }
+
+    /// An operator after its operand, such as a call or index,
+    /// the operator's result is passed to f
+    pub fn postfix<P, F>(mut self, bp: u32, p: P, f: F) -> Self
+    where
+        P: Parser<'a> + 'a,
+        F: Fn(V, P::Out) -> V + 'a,
+    {
+        self.post.push((
+            bp * 2,
+            Box::new(move |_, it, lhs| match p.parse(it) {
+                Ok((i2, o, ex)) => Ok(Ok((i2, f(lhs, o), ex))),
+                Err(e) => Err((lhs, e)),
+            }),
+        ));
+        self
+    }
+
+    /// `cond open mid close rhs`, the middle is a full expression, and it groups to the right.
+    pub fn ternary<P, Q, F>(mut self, bp: u32, open: P, close: Q, f: F) -> Self
+    where
+        P: Parser<'a> + 'a,
+        Q: Parser<'a> + 'a,
+        F: Fn(V, V, V) -> V + 'a,
+    {
+        self.post.push((
+            bp * 2 + 1,
+            Box::new(move |pr, it, lhs| {
+                let i2 = match open.parse(it) {
+                    Ok((i2, _, _)) => i2,
+                    Err(e) => return Err((lhs, e)),
+                };
+                Ok((|| {
+                    let (i3, mid, ex) = pr.expr(&i2, 0)?;
+                    let (i4, _, _) = close.parse(&i3).join_err_op(ex)?;
+                    let (i5, rhs, ex) = pr.expr(&i4, bp * 2)?;
+                    Ok((i5, f(lhs, mid, rhs), ex))
+                })())
+            }),
+        ));
+        self
+    }
+
+    fn operand(&self, it: &PIter<'a>) -> ParseRes<'a, V> {
+        let mut err = None;
+        for p in &self.prefix {
+            match p(self, it) {
+                Ok(r) => return r,
+                Err(e) => err = Some(longer_op(err, op_err(it, e, "operand"))),
+            }
+        }
+        match (self.atom)(it) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(longer_op(err, op_err(it, e, "operand"))),
+        }
+    }
+
+    fn expr(&self, it: &PIter<'a>, min_bp: u32) -> ParseRes<'a, V> {
+        let (mut it, mut lhs, _) = self.operand(it)?;
+        'ops: loop {
+            let mut err = None;
+            for (lbp, op) in &self.post {
+                if *lbp < min_bp {
+                    continue;
+                }
+                match op(self, &it, lhs) {
+                    Ok(r) => {
+                        let (i2, v, _) = r?;
+                        it = i2;
+                        lhs = v;
+                        continue 'ops;
+                    }
+                    Err((v, e)) => {
+                        lhs = v;
+                        err = Some(longer_op(err, op_err(&it, e, "operator")));
+                    }
+                }
+            }
+            let ex = err.unwrap_or_else(|| it.err_s("operator"));
+            return Ok((it, lhs, Some(ex)));
+        }
+    }
+}
+
+impl<'a, V: 'a> Parser<'a> for Pratt<'a, V> {
+    type Out = V;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, V> {
+        self.expr(it, 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    fn calc<'a>() -> Pratt<'a, i64> {
+        pratt(ws_(common::Int).map(|n| n as i64))
+            .infix_left(1, ws_('+'), |a, _, b| a + b)
+            .infix_left(1, ws_('-'), |a, _, b| a - b)
+            .infix_left(2, ws_('*'), |a, _, b| a * b)
+            .prefix(3, ws_('-'), |_, v| -v)
+            .postfix(4, middle(ws_('['), ws_(common::Int), ws_(']')), |v, n| {
+                v * n as i64
+            })
+    }
+
+    #[test]
+    fn test_calc_and_errors() {
+        assert_eq!(calc().parse_s("2 - 3 * 4 - -1"), Ok(-9));
+        assert_eq!(calc().parse_s("-2[3] * 2"), Ok(-12));
+        let e = (calc(), eoi).parse_s("2 + 3 4").unwrap_err();
+        assert_eq!(e.index, Some(6));
+        let e = calc().parse_s("2 +").unwrap_err();
+        assert_eq!((e.exp, e.index), (Expected::Str("operand"), None));
+        // a postfix that started but failed part way is reported where it failed
+        let e = (calc(), eoi).parse_s("2[3").unwrap_err();
+        assert_eq!(e.index, None);
+    }
+}
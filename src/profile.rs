@@ -0,0 +1,255 @@
+//! Counting and timing rules, to find where a grammar spends its time.
+//!
+//! While [profiled] runs, every rule made by [parser!] is counted by name. The [Profile] shows
+//! which rules are called most, fail most, or throw away the most input, which are the places
+//! to try [crate::memo] or to reorder alternatives.
+//!
+//! ```rust
+//! use bogobble::*;
+//! parser!((Num->isize) common::Int);
+//! parser!((Word->String) common::Ident);
+//! parser!((Item->String) ws_(or(Word, Num.map(|n| n.to_string()))));
+//!
+//! let (r, prof) = profiled(|| star(Item).parse_s("1 2 a 345"));
+//! assert_eq!(r.unwrap().len(), 4);
+//! let word = prof.get("Word").unwrap();
+//! assert_eq!((word.calls, word.successes, word.failures), (5, 1, 4));
+//! let num = prof.get("Num").unwrap();
+//! // Num is only tried once Word has failed, including at the end of input
+//! assert_eq!((num.calls, num.failures), (4, 1));
+//!
+//! let report = prof.report(ProfileSort::Calls);
+//! assert!(report.starts_with("rule"));
+//! assert!(report.lines().any(|l| l.starts_with("Word ")));
+//! assert_eq!(prof.sorted(ProfileSort::Calls)[0].calls, 5);
+//! ```
+//!
+//! Like [crate::traced], this needs the `trace` feature.
+use crate::hooks::{hooks, set_hooks, PROFILING};
+use crate::iter::*;
+use crate::parser::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// The totals for one rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RuleStats {
+    pub name: &'static str,
+    pub calls: usize,
+    pub successes: usize,
+    pub failures: usize,
+    /// Bytes read by failed calls before they failed, so parsed again by something else
+    pub backtracked: usize,
+    /// Time inside the rule, including the rules it calls.
+    /// Recursive calls are counted at each level.
+    pub total: Duration,
+    /// Time inside the rule, not counting the rules it calls
+    pub own: Duration,
+}
+
+/// The order of [Profile::report], largest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProfileSort {
+    Calls,
+    Failures,
+    Backtracked,
+    Total,
+    #[default]
+    Own,
+}
+
+/// The stats for each rule called during [profiled]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Profile {
+    pub rules: Vec<RuleStats>,
+}
+
+thread_local! {
+    static STATS: RefCell<HashMap<&'static str, RuleStats>> = RefCell::new(HashMap::new());
+    /// Time spent in the rules called by each running rule
+    static CHILD_TIME: RefCell<Vec<Duration>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Puts back the outer profile when dropped, even if the profiled function panics
+struct Restore {
+    was: u8,
+    stats: HashMap<&'static str, RuleStats>,
+    child: Vec<Duration>,
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        set_hooks(PROFILING, self.was);
+        let stats = std::mem::take(&mut self.stats);
+        STATS.with(|s| s.replace(stats));
+        let child = std::mem::take(&mut self.child);
+        CHILD_TIME.with(|c| c.replace(child));
+    }
+}
+
+/// Runs f, counting the rules called on this thread
+pub fn profiled<R, F: FnOnce() -> R>(f: F) -> (R, Profile) {
+    let restore = Restore {
+        was: set_hooks(PROFILING, PROFILING),
+        stats: STATS.with(|s| s.replace(HashMap::new())),
+        child: CHILD_TIME.with(|c| c.replace(Vec::new())),
+    };
+    let r = f();
+    let stats = STATS.with(|s| s.take());
+    drop(restore);
+    let mut rules: Vec<RuleStats> = stats.into_values().collect();
+    rules.sort_by_key(|r| r.name);
+    (r, Profile { rules })
+}
+
+pub fn is_profiling() -> bool {
+    hooks() & PROFILING > 0
+}
+
+/// Counts one call of the named rule, used through the hook in [parser!]
+pub(crate) fn profile_rule<'a, V, F: FnOnce() -> ParseRes<'a, V>>(
+    name: &'static str,
+    it: &PIter<'a>,
+    f: F,
+) -> ParseRes<'a, V> {
+    CHILD_TIME.with(|c| c.borrow_mut().push(Duration::ZERO));
+    let start = Instant::now();
+    let r = f();
+    let took = start.elapsed();
+    let children = CHILD_TIME.with(|c| {
+        let mut c = c.borrow_mut();
+        let children = c.pop().unwrap_or_default();
+        if let Some(parent) = c.last_mut() {
+            *parent += took;
+        }
+        children
+    });
+    STATS.with(|s| {
+        let mut s = s.borrow_mut();
+        let st = s.entry(name).or_insert(RuleStats {
+            name,
+            ..RuleStats::default()
+        });
+        st.calls += 1;
+        st.total += took;
+        st.own += took.saturating_sub(children);
+        match &r {
+            Ok(_) => st.successes += 1,
+            Err(e) => {
+                st.failures += 1;
+                // The end covers what was read, even when a rule moves the index to its start
+                let fail_at = e.end.unwrap_or(it.orig_str().len());
+                st.backtracked += fail_at.saturating_sub(it.offset());
+            }
+        }
+    });
+    r
+}
+
+impl Profile {
+    pub fn get(&self, name: &str) -> Option<&RuleStats> {
+        self.rules.iter().find(|r| r.name == name)
+    }
+
+    /// The rules ordered by the sort, largest first, ties by name
+    pub fn sorted(&self, sort: ProfileSort) -> Vec<RuleStats> {
+        let mut res = self.rules.clone();
+        res.sort_by(|a, b| {
+            let key = |r: &RuleStats| match sort {
+                ProfileSort::Calls => r.calls as u128,
+                ProfileSort::Failures => r.failures as u128,
+                ProfileSort::Backtracked => r.backtracked as u128,
+                ProfileSort::Total => r.total.as_nanos(),
+                ProfileSort::Own => r.own.as_nanos(),
+            };
+            key(b).cmp(&key(a)).then(a.name.cmp(b.name))
+        });
+        res
+    }
+
+    /// A table with a header line, then one line per rule, times are in microseconds
+    pub fn report(&self, sort: ProfileSort) -> String {
+        let rows = self.sorted(sort);
+        let w = rows.iter().map(|r| r.name.len()).max().unwrap_or(0).max(4);
+        let mut s = String::new();
+        let _ = writeln!(
+            s,
+            "{:w$} {:>8} {:>8} {:>8} {:>11} {:>10} {:>10}",
+            "rule",
+            "calls",
+            "ok",
+            "fail",
+            "backtracked",
+            "total_us",
+            "own_us",
+            w = w
+        );
+        for r in rows {
+            let _ = writeln!(
+                s,
+                "{:w$} {:>8} {:>8} {:>8} {:>11} {:>10} {:>10}",
+                r.name,
+                r.calls,
+                r.successes,
+                r.failures,
+                r.backtracked,
+                r.total.as_micros(),
+                r.own.as_micros(),
+                w = w
+            );
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    parser!((Pair->(&'static str, &'static str)) ("ab", "cd"));
+    parser!((Either->&'static str) or(Pair.map(|p| p.0), "abx"));
+    parser!((Greet->&'static str) or("hello", "help"));
+
+    #[test]
+    fn test_backtracked_and_nesting() {
+        let (r, prof) = profiled(|| Either.parse_s("abx"));
+        assert_eq!(r, Ok("abx"));
+        let pair = prof.get("Pair").unwrap();
+        // "cd" read the 'x' before failing
+        assert_eq!((pair.failures, pair.backtracked), (1, 3));
+        let either = prof.get("Either").unwrap();
+        assert!(either.total >= either.own && either.total >= pair.total);
+        let names: Vec<_> = prof
+            .sorted(ProfileSort::Backtracked)
+            .iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, vec!["Pair", "Either"]);
+        // nothing is counted outside profiled
+        assert!(!is_profiling());
+        assert_eq!(profiled(|| Either.parse_s("abcd")).1.rules.len(), 2);
+    }
+
+    #[test]
+    fn test_backtracked_from_rule_start() {
+        let (r, prof) = profiled(|| Greet.parse_s("helxo"));
+        assert_eq!(r.unwrap_err().index, Some(0));
+        assert_eq!(prof.get("Greet").unwrap().backtracked, 4);
+    }
+
+    #[test]
+    fn test_profile_panic_restores() {
+        let (_, prof) = profiled(|| {
+            let r = std::panic::catch_unwind(|| {
+                profiled(|| Pair.map(|_| panic!("inside")).parse_s("abcd"))
+            });
+            assert!(r.is_err());
+            assert!(is_profiling());
+            Either.parse_s("abx")
+        });
+        assert_eq!(prof.get("Pair").map(|p| p.calls), Some(1));
+        assert!(!is_profiling());
+    }
+}
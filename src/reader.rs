@@ -4,6 +4,8 @@ use crate::err::*;
 use crate::iter::*;
 use crate::parser::*;
 use crate::select::*;
+use crate::source::FileId;
+use crate::span::Span;
 
 pub type StrPos = Pos<()>;
 
@@ -11,19 +13,16 @@ pub type StrPos = Pos<()>;
 pub struct Pos<O> {
     pub line: usize,
     pub col: usize,
-    pub start: Option<usize>,
-    pub fin: Option<usize>,
+    pub span: Span,
     pub ob: O,
+    pub file: Option<FileId>,
 }
 
 impl<O> Pos<O> {
-    ///This version assumes that this is the string it came from
+    ///This version assumes that this is the string it came from,
+    ///see [crate::SourceMap::pos_str] for a checked version
     pub fn on_str<'a>(&self, s: &'a str) -> &'a str {
-        match (self.start, self.fin) {
-            (Some(st), Some(f)) => &s[st..f],
-            (Some(st), None) => &s[st..],
-            _ => "",
-        }
+        self.span.on_str(s)
     }
 }
 
@@ -35,17 +34,15 @@ impl<'a, P: Parser<'a>> Parser<'a> for PPos<P> {
     type Out = Pos<P::Out>;
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
         let (line, col) = it.lc();
-        let start = it.index();
         let (rit, r, pex) = self.p.parse(it)?;
-        let fin = rit.index();
         Ok((
             rit,
             Pos {
                 line,
                 col,
-                start,
-                fin,
+                span: Span::new(it.offset(), rit.offset()),
                 ob: r,
+                file: it.file(),
             },
             pex,
         ))
@@ -56,7 +53,7 @@ impl<'a, P: Parser<'a>> Parser<'a> for PPos<P> {
 /// use bogobble::*;
 /// let s = " \n  hello   ".to_string();
 /// let v = last("\n ".istar(),pos_ig(Alpha.istar())).parse_s(&s).unwrap();
-/// assert_eq!(v,Pos{line:1,col:2,start:Some(4),fin:Some(9),ob:()});
+/// assert_eq!(v,Pos{line:1,col:2,span:Span::new(4,9),ob:(),file:None});
 /// assert_eq!(v.on_str(&s),"hello");
 /// ```
 pub fn pos_ig<'a, P: Parser<'a>>(p: P) -> PPos<Ig<P>> {
@@ -148,8 +145,8 @@ parser! {(EOI->())
 }
 
 pub fn eoi<'a>(i: &PIter<'a>) -> ParseRes<'a, ()> {
-    let mut r = i.clone();
-    if r.next() == None {
+    let mut r = *i;
+    if r.next().is_none() {
         return Ok((r, (), None));
     }
     i.err_r(Expected::EOI)
@@ -167,7 +164,7 @@ impl<'a, P: Parser<'a>> Parser<'a> for Peek<P> {
     type Out = P::Out;
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, P::Out> {
         let (_, v, c) = self.p.parse(it)?;
-        Ok((it.clone(), v, c))
+        Ok((*it, v, c))
     }
 }
 
@@ -184,7 +181,7 @@ impl<'a, A: Parser<'a, Out = char>, B: Parser<'a>> Parser<'a> for CharsUntil<A,
     type Out = (String, B::Out);
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
         let mut res = String::new();
-        let mut it = it.clone();
+        let mut it = *it;
         loop {
             //let it2 = it.clone();
             if let Ok((i, bv, c1)) = self.b.parse(&it) {
@@ -217,7 +214,7 @@ impl<'a, A: Parser<'a, Out = AV>, AV: Into<String> + AsRef<str>> Parser<'a> for
             Ok((it2, ss, _)) => (it2, ss.into()),
             Err(e) => {
                 if self.min == 0 {
-                    return Ok((it.clone(), String::new(), Some(e)));
+                    return Ok((*it, String::new(), Some(e)));
                 } else {
                     return Err(e);
                 }
@@ -0,0 +1,356 @@
+//! Error recovery, for reporting every mistake in one run.
+//!
+//! [recover] catches a failure, skips the bad input with another parser, usually [skip_until]
+//! or [skip_past], and puts a fallback value in its place. The error is kept in the
+//! [Recovered] result alongside the value. [sep_star_recover] and [star_until_recover] do the
+//! same for each element of a list.
+//!
+//! ```rust
+//! use bogobble::*;
+//! let entry = (ws_(common::Ident), ws_('='), ws_(common::Int), ws_(';'));
+//! let entry = entry.map(|(k, _, v, _)| (k, v));
+//! let conf = star_until_recover(entry, ws_(eoi), skip_past(';'));
+//!
+//! let r = conf.parse_s("a = 1; b = x; c = 3; d 4; e = 5;").unwrap();
+//! assert_eq!(r.val.0, vec![("a".to_string(), 1), ("c".to_string(), 3), ("e".to_string(), 5)]);
+//! assert_eq!(r.errs.len(), 2);
+//! assert_eq!(r.errs[0].index, Some(11));
+//! assert_eq!(r.errs[1].index, Some(23));
+//!
+//! let num = recover(common::Int, skip_until(or(',', ']')), || 0);
+//! let list = middle('[', sep_star(ws_(num), ','), ']');
+//! let r = Recovered::new(list.parse_s("[1, x2, 4]").unwrap()).flatten();
+//! assert_eq!(r.val, vec![1, 0, 4]);
+//! assert_eq!(r.errs.len(), 1);
+//! ```
+use crate::err::*;
+use crate::iter::*;
+use crate::parser::*;
+
+/// A value that may have been partly rebuilt after errors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovered<'a, V> {
+    pub val: V,
+    /// The errors recovered from, in the order they were found
+    pub errs: Vec<PErr<'a>>,
+}
+
+impl<'a, V> Recovered<'a, V> {
+    pub fn new(val: V) -> Self {
+        Recovered {
+            val,
+            errs: Vec::new(),
+        }
+    }
+
+    /// True if nothing needed recovering
+    pub fn is_clean(&self) -> bool {
+        self.errs.is_empty()
+    }
+
+    pub fn map<B, F: FnOnce(V) -> B>(self, f: F) -> Recovered<'a, B> {
+        Recovered {
+            val: f(self.val),
+            errs: self.errs,
+        }
+    }
+
+    /// The value if there were no errors, otherwise all of the errors
+    pub fn into_result(self) -> Result<V, Vec<PErr<'a>>> {
+        match self.errs.is_empty() {
+            true => Ok(self.val),
+            false => Err(self.errs),
+        }
+    }
+}
+
+impl<'a, V> Recovered<'a, Vec<Recovered<'a, V>>> {
+    /// Gathers the errors of each item, in order, after those of the list itself
+    pub fn flatten(self) -> Recovered<'a, Vec<V>> {
+        let mut errs = self.errs;
+        let mut val = Vec::with_capacity(self.val.len());
+        for r in self.val {
+            errs.extend(r.errs);
+            val.push(r.val);
+        }
+        Recovered { val, errs }
+    }
+}
+
+impl<'a, V> Parsed<'a, Recovered<'a, V>> {
+    /// Moves the recovered errors into the warnings
+    pub fn take_recovered(self) -> Parsed<'a, V> {
+        let mut warnings = self.warnings;
+        warnings.extend(self.val.errs);
+        Parsed {
+            val: self.val.val,
+            warnings,
+            consumed: self.consumed,
+        }
+    }
+}
+
+pub struct SkipUntil<P> {
+    sync: P,
+}
+
+/// Consumes input up to, but not including, the first point where sync matches, or to the end
+/// of input. Returns the skipped text and never fails.
+pub fn skip_until<'a, P: Parser<'a>>(sync: P) -> SkipUntil<P> {
+    SkipUntil { sync }
+}
+
+impl<'a, P: Parser<'a>> Parser<'a> for SkipUntil<P> {
+    type Out = &'a str;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, &'a str> {
+        let mut ri = *it;
+        while !ri.eoi() && self.sync.parse(&ri).is_err() {
+            ri.next();
+        }
+        Ok((ri, it.str_to(ri.index()), None))
+    }
+}
+
+pub struct SkipPast<P> {
+    sync: P,
+}
+
+/// As [skip_until], but sync is consumed too when found.
+/// The skipped text does not include it.
+pub fn skip_past<'a, P: Parser<'a>>(sync: P) -> SkipPast<P> {
+    SkipPast { sync }
+}
+
+impl<'a, P: Parser<'a>> Parser<'a> for SkipPast<P> {
+    type Out = &'a str;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, &'a str> {
+        let mut ri = *it;
+        loop {
+            if let Ok((r, _, _)) = self.sync.parse(&ri) {
+                return Ok((r, it.str_to(ri.index()), None));
+            }
+            if ri.eoi() {
+                return Ok((ri, it.as_str(), None));
+            }
+            ri.next();
+        }
+    }
+}
+
+pub struct Recover<P, S, F> {
+    p: P,
+    skip: S,
+    fallback: F,
+}
+
+/// On failure of p, skips from where p started using skip, and returns fallback() with the
+/// error. If skip fails or consumes nothing, the original error is returned instead, so
+/// repeating a recover always makes progress.
+pub fn recover<'a, P, S, F>(p: P, skip: S, fallback: F) -> Recover<P, S, F>
+where
+    P: Parser<'a>,
+    S: Parser<'a>,
+    F: Fn() -> P::Out,
+{
+    Recover { p, skip, fallback }
+}
+
+impl<'a, P, S, F> Parser<'a> for Recover<P, S, F>
+where
+    P: Parser<'a>,
+    S: Parser<'a>,
+    F: Fn() -> P::Out,
+{
+    type Out = Recovered<'a, P::Out>;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
+        let e = match self.p.parse(it) {
+            Ok((r, v, ex)) => return Ok((r, Recovered::new(v), ex)),
+            Err(e) => e,
+        };
+        match self.skip.parse(it) {
+            Ok((r, _, _)) if r.index() != it.index() => Ok((
+                r,
+                Recovered {
+                    val: (self.fallback)(),
+                    errs: vec![e],
+                },
+                None,
+            )),
+            _ => Err(e),
+        }
+    }
+}
+
+pub struct SepStarRecover<A, B, S> {
+    a: A,
+    b: B,
+    skip: S,
+}
+
+/// As [crate::sep_star], but an element that fails is skipped with skip, its error is kept,
+/// and the list continues at the next separator. A first element that fails without skip
+/// consuming anything means the list is empty.
+pub fn sep_star_recover<'a, A, B, S>(a: A, b: B, skip: S) -> SepStarRecover<A, B, S>
+where
+    A: Parser<'a>,
+    B: Parser<'a>,
+    S: Parser<'a>,
+{
+    SepStarRecover { a, b, skip }
+}
+
+impl<'a, A, B, S> Parser<'a> for SepStarRecover<A, B, S>
+where
+    A: Parser<'a>,
+    B: Parser<'a>,
+    S: Parser<'a>,
+{
+    type Out = Recovered<'a, Vec<A::Out>>;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
+        let mut res = Recovered::new(Vec::new());
+        let mut ri = *it;
+        let mut first = true;
+        loop {
+            let mut stuck = None;
+            ri = match self.a.parse(&ri) {
+                Ok((r, v, _)) => {
+                    res.val.push(v);
+                    r
+                }
+                Err(e) => {
+                    let r = match self.skip.parse(&ri) {
+                        Ok((r, _, _)) => r,
+                        Err(_) => ri,
+                    };
+                    if r.index() != ri.index() {
+                        res.errs.push(e);
+                    } else if first {
+                        return Ok((ri, res, Some(e)));
+                    } else {
+                        stuck = Some(e);
+                    }
+                    r
+                }
+            };
+            first = false;
+            ri = match (self.b.parse(&ri), stuck) {
+                // Nothing moved, so trying again would fail the same way
+                (Ok((r, _, _)), Some(e)) if r.index() == ri.index() => {
+                    return Ok((ri, res, Some(e)))
+                }
+                (Ok((r, _, _)), stuck) => {
+                    res.errs.extend(stuck);
+                    r
+                }
+                (Err(e), stuck) => {
+                    res.errs.extend(stuck);
+                    return Ok((ri, res, Some(e)));
+                }
+            };
+        }
+    }
+}
+
+pub struct StarUntilRecover<A, B, S> {
+    a: A,
+    b: B,
+    skip: S,
+}
+
+/// As [crate::star_until], but an element that fails is skipped with skip and its error kept.
+/// Fails if skip cannot make progress before b is found, usually at the end of input.
+pub fn star_until_recover<'a, A, B, S>(a: A, b: B, skip: S) -> StarUntilRecover<A, B, S>
+where
+    A: Parser<'a>,
+    B: Parser<'a>,
+    S: Parser<'a>,
+{
+    StarUntilRecover { a, b, skip }
+}
+
+impl<'a, A, B, S> Parser<'a> for StarUntilRecover<A, B, S>
+where
+    A: Parser<'a>,
+    B: Parser<'a>,
+    S: Parser<'a>,
+{
+    type Out = Recovered<'a, (Vec<A::Out>, B::Out)>;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
+        let mut vals = Vec::new();
+        let mut errs = Vec::new();
+        let mut ri = *it;
+        loop {
+            let b_err = match self.b.parse(&ri) {
+                Ok((r, v, _)) => {
+                    let val = (vals, v);
+                    return Ok((r, Recovered { val, errs }, None));
+                }
+                Err(e) => e,
+            };
+            ri = match self.a.parse(&ri) {
+                Ok((r, _, _)) if r.index() == ri.index() => {
+                    return Err(r.err_s("To Consume some Data"))
+                }
+                Ok((r, v, _)) => {
+                    vals.push(v);
+                    r
+                }
+                Err(e) => match self.skip.parse(&ri) {
+                    Ok((r, _, _)) if r.index() != ri.index() => {
+                        errs.push(e);
+                        r
+                    }
+                    _ => return Err(e.join(b_err)),
+                },
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_sep_star_recover() {
+        let p = sep_star_recover(ws_(common::Int), ws_(','), skip_until(or(',', ']')));
+        let list = middle('[', p, ws_(']'));
+        let r = list.parse_s("[]").unwrap();
+        assert!(r.is_clean() && r.val.is_empty());
+        let r = list.parse_s("[1, a, 3,,5 ]").unwrap();
+        assert_eq!(r.val, vec![1, 3, 5]);
+        let idx: Vec<_> = r.errs.iter().map(|e| e.index).collect();
+        assert_eq!(idx, vec![Some(4), Some(9)]);
+        // a trailing separator is reported, not fatal
+        let errs = list.parse_s("[4,]").unwrap().into_result().unwrap_err();
+        assert_eq!(errs[0].index, Some(3));
+        let p = sep_star_recover(common::Int, ',', skip_until(','));
+        let r = p.parse_all("1,x,3").unwrap().take_recovered();
+        assert_eq!((r.val, r.warnings.len(), r.consumed), (vec![1, 3], 1, 5));
+        let r = p.parse_full(&PIter::at("1,x,3", 2)).unwrap();
+        assert_eq!((r.val.val, r.consumed), (vec![3], 3));
+    }
+
+    #[test]
+    fn test_sep_star_recover_stops_when_stuck() {
+        // at ';' neither the skip nor the separator move, so the list ends there
+        let p = sep_star_recover(common::Int, maybe(','), skip_until(';'));
+        let r = first(p, ';').parse_s("1,x;").unwrap();
+        assert_eq!((r.val, r.errs.len()), (vec![1], 1));
+    }
+
+    #[test]
+    fn test_recover_needs_progress() {
+        let item = || recover(first(common::Int, ';'), skip_past(';'), || -1);
+        let r = star(item()).parse_s("1;b;3;").unwrap();
+        let r = Recovered::new(r).flatten();
+        assert_eq!(r.val, vec![1, -1, 3]);
+        assert_eq!(r.errs.len(), 1);
+        // nothing left to skip at the end, so the error is returned as normal
+        assert!(item().parse_s("").is_err());
+        let e = star_until_recover(one_char(NumDigit), ';', skip_past(','))
+            .parse_s("12,x3")
+            .unwrap_err();
+        assert_eq!(e.index, None);
+    }
+}
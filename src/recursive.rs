@@ -0,0 +1,148 @@
+//! Self referential parsers without naming a struct for each rule.
+//!
+//! [recursive] passes the parser being built a handle to itself. For rules that refer to each
+//! other, [forward] declares a rule that is defined later.
+//!
+//! ```rust
+//! use bogobble::*;
+//! #[derive(Debug, PartialEq)]
+//! enum Tree {
+//!     Leaf(isize),
+//!     List(Vec<Tree>),
+//! }
+//! let tree = recursive(|tree| {
+//!     or(
+//!         common::Int.map(Tree::Leaf),
+//!         middle('[', sep_star(last(WSL.istar(), tree), ','), ']').map(Tree::List),
+//!     )
+//! });
+//! assert_eq!(
+//!     tree.parse_s("[1,[2, 3],[]]"),
+//!     Ok(Tree::List(vec![
+//!         Tree::Leaf(1),
+//!         Tree::List(vec![Tree::Leaf(2), Tree::Leaf(3)]),
+//!         Tree::List(vec![]),
+//!     ]))
+//! );
+//!
+//! // Mutually recursive rules, "a" is always followed by a "b" rule and the other way round
+//! let a = forward();
+//! let b = forward();
+//! a.define(or(('a', b.weak()).map(|(_, n)| n + 1), "".asv(0)));
+//! b.define(('b', a.weak()).map(|(_, n)| n + 1));
+//! assert_eq!(a.parse_s("ababab"), Ok(6));
+//! assert_eq!(a.parse_s("abaa"), Ok(2));
+//! assert!((a.clone(), eoi).parse_s("abaa").is_err());
+//! ```
+use crate::dynamic::BoxParser;
+use crate::iter::*;
+use crate::parser::*;
+use std::cell::OnceCell;
+use std::rc::{Rc, Weak};
+
+type Slot<'a, O> = OnceCell<BoxParser<'a, O>>;
+
+/// A rule that can be used before it is defined, see [forward].
+/// Cloning it gives another handle to the same rule.
+pub struct Forward<'a, O> {
+    slot: Rc<Slot<'a, O>>,
+}
+
+impl<'a, O> Clone for Forward<'a, O> {
+    fn clone(&self) -> Self {
+        Forward {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+/// Declares a rule, so parsers can refer to it before [Forward::define] is called.
+///
+/// A rule that holds a strong handle to itself is never freed, so inside definitions use
+/// [Forward::weak], and keep the Forwards themselves alive while parsing.
+pub fn forward<'a, O>() -> Forward<'a, O> {
+    Forward {
+        slot: Rc::new(OnceCell::new()),
+    }
+}
+
+impl<'a, O> Forward<'a, O> {
+    /// Panics if the rule has already been defined
+    pub fn define<P: Parser<'a, Out = O> + 'a>(&self, p: P) {
+        if self.slot.set(p.boxed()).is_err() {
+            panic!("Forward rule defined twice");
+        }
+    }
+
+    /// A handle that does not keep the rule alive
+    pub fn weak(&self) -> WeakRule<'a, O> {
+        WeakRule {
+            slot: Rc::downgrade(&self.slot),
+        }
+    }
+}
+
+impl<'a, O> Parser<'a> for Forward<'a, O> {
+    type Out = O;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, O> {
+        match self.slot.get() {
+            Some(p) => p.parse(it),
+            None => panic!("Forward rule used before it was defined"),
+        }
+    }
+}
+
+/// A handle to a rule from [recursive] or [Forward::weak]
+pub struct WeakRule<'a, O> {
+    slot: Weak<Slot<'a, O>>,
+}
+
+impl<'a, O> Clone for WeakRule<'a, O> {
+    fn clone(&self) -> Self {
+        WeakRule {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<'a, O> Parser<'a> for WeakRule<'a, O> {
+    type Out = O;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, O> {
+        let slot = self
+            .slot
+            .upgrade()
+            .expect("Recursive rule used after it was dropped");
+        match slot.get() {
+            Some(p) => p.parse(it),
+            None => panic!("Recursive rule used before it was defined"),
+        }
+    }
+}
+
+/// Builds a parser that can refer to itself through the handle passed to f
+pub fn recursive<'a, O, P, F>(f: F) -> Forward<'a, O>
+where
+    P: Parser<'a, Out = O> + 'a,
+    F: FnOnce(WeakRule<'a, O>) -> P,
+{
+    let r = forward();
+    r.define(f(r.weak()));
+    r
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_recursive_is_freed() {
+        let p = recursive(|p| or(middle('(', p, ')').map(|n| n + 1), "".asv(0)));
+        assert_eq!(p.parse_s("((()))"), Ok(3));
+        let w = p.weak();
+        let p2 = p.clone();
+        drop(p);
+        assert_eq!(w.parse_s("()"), Ok(1));
+        drop(p2);
+        assert!(w.slot.upgrade().is_none());
+    }
+}
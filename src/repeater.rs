@@ -6,8 +6,8 @@ use crate::tuple::*;
 
 #[derive(Clone)]
 pub struct Exact<A> {
-    n: usize,
-    a: A,
+    pub(crate) n: usize,
+    pub(crate) a: A,
 }
 
 impl<'a, A: Parser<'a>> Parser<'a> for Exact<A> {
@@ -81,7 +81,7 @@ fn do_sep<'a, A: Parser<'a>, B: Parser<'a>>(
     exact: bool,
 ) -> ParseRes<'a, Vec<A::Out>> {
     let mut res = Vec::new();
-    let mut ri = i.clone();
+    let mut ri = *i;
     //TODO  consider wraping this error as parent
     loop {
         ri = match a.parse(&ri) {
@@ -89,8 +89,9 @@ fn do_sep<'a, A: Parser<'a>, B: Parser<'a>>(
                 res.push(v);
                 r
             }
+            Err(e) if e.is_cut => return Err(e),
             Err(e) => {
-                if res.len() == 0 && min == 0 {
+                if res.is_empty() && min == 0 {
                     return Ok((ri, res, Some(e)));
                 }
                 if res.len() == min && exact {
@@ -103,7 +104,7 @@ fn do_sep<'a, A: Parser<'a>, B: Parser<'a>>(
         ri = match b.parse(&ri) {
             Ok((r, _, _)) => r,
             Err(e) => {
-                if res.len() < min {
+                if res.len() < min || e.is_cut {
                     return Err(e);
                 } else {
                     return Ok((ri, res, Some(e)));
@@ -115,8 +116,8 @@ fn do_sep<'a, A: Parser<'a>, B: Parser<'a>>(
 
 #[derive(Clone)]
 pub struct SepStar<A, B> {
-    a: A,
-    b: B,
+    pub(crate) a: A,
+    pub(crate) b: B,
 }
 
 impl<'a, A, B> Parser<'a> for SepStar<A, B>
@@ -139,8 +140,8 @@ pub fn sep_plus<'a, A: Parser<'a>, B: Parser<'a>>(a: A, b: B) -> SepPlus<A, B> {
 
 #[derive(Clone)]
 pub struct SepPlus<A, B> {
-    a: A,
-    b: B,
+    pub(crate) a: A,
+    pub(crate) b: B,
 }
 
 impl<'a, A, B> Parser<'a> for SepPlus<A, B>
@@ -160,14 +161,14 @@ pub fn do_rep<'a, A: Parser<'a>>(
     min: usize,
     exact: bool,
 ) -> ParseRes<'a, Vec<A::Out>> {
-    let mut it = i.clone();
+    let mut it = *i;
     let mut res = Vec::new();
 
     loop {
         match a.parse(&it) {
             Ok((i2, v, _)) => {
                 res.push(v);
-                if it.lc() == i2.lc() && !exact {
+                if it.index() == i2.index() && !exact {
                     return Err(it.err_s("To Consume some data"));
                 }
                 if res.len() == min && exact {
@@ -176,7 +177,7 @@ pub fn do_rep<'a, A: Parser<'a>>(
                 it = i2;
             }
             Err(e) => {
-                if res.len() >= min {
+                if res.len() >= min && !e.is_cut {
                     return Ok((it, res, Some(e)));
                 }
                 return Err(e);
@@ -219,7 +220,7 @@ fn do_repeat_until<'a, A: Parser<'a>, B: Parser<'a>>(
     a: &A,
     b: &B,
 ) -> ParseRes<'a, (Vec<A::Out>, B::Out)> {
-    let mut ri = it.clone();
+    let mut ri = *it;
     let mut res = Vec::new();
     let mut done = 0;
     loop {
@@ -232,7 +233,7 @@ fn do_repeat_until<'a, A: Parser<'a>, B: Parser<'a>>(
         };
         ri = match a.parse(&ri) {
             Ok((r, v, _)) => {
-                if r.lc() == ri.lc() {
+                if r.index() == ri.index() {
                     return Err(r.err_s("To Consume some Data"));
                 }
                 res.push(v);
@@ -288,9 +289,9 @@ pub fn plus_until_ig<'a, A: Parser<'a>, B: Parser<'a>>(a: A, b: B) -> FirstRes<P
 }
 
 pub struct SepUntil<A, B, C> {
-    a: A,
-    b: B,
-    c: C,
+    pub(crate) a: A,
+    pub(crate) b: B,
+    pub(crate) c: C,
 }
 
 impl<'a, A, B, C> Parser<'a> for SepUntil<A, B, C>
@@ -301,11 +302,10 @@ where
 {
     type Out = (Vec<A::Out>, C::Out);
     fn parse(&self, i: &PIter<'a>) -> ParseRes<'a, Self::Out> {
-        let mut ri = i.clone();
+        let mut ri = *i;
         let mut res = Vec::new();
-        match self.c.parse(&ri) {
-            Ok((r, v, _)) => return Ok((r, (res, v), None)),
-            Err(_) => {}
+        if let Ok((r, v, _)) = self.c.parse(&ri) {
+            return Ok((r, (res, v), None));
         }
         loop {
             ri = match self.a.parse(&ri) {
@@ -352,7 +352,7 @@ where
 }
 
 #[cfg(test)]
-pub mod test {
+mod test {
     use super::*;
     //use crate::ptrait::*;
     use crate::*;
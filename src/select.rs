@@ -1,3 +1,4 @@
+use crate::hooks::trace_combi;
 use crate::iter::*;
 use crate::parser::*;
 
@@ -97,7 +98,7 @@ where
 {
     type Out = V;
     fn parse(&self, i: &PIter<'a>) -> ParseRes<'a, V> {
-        match self.a.parse(i) {
+        trace_combi("or", i, || match self.a.parse(i) {
             Ok((r, v, e)) => Ok((r, v, e)),
             Err(e) if e.is_break => Err(e),
             Err(e) => match self.b.parse(i) {
@@ -105,6 +106,6 @@ where
                 Err(e2) if e2.is_break => Err(e2),
                 Err(e2) => Err(e.longer(e2)),
             },
-        }
+        })
     }
 }
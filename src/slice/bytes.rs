@@ -0,0 +1,333 @@
+//! Byte parsers for use with [SParser].
+//!
+//! ```rust
+//! use bogobble::slice::{self, bytes::*, SParser};
+//! // a length prefixed, tagged record
+//! let p = (b'T', AnyByte.one(), le_u16).map(|(_, tag, n)| (tag, n));
+//! assert_eq!(p.parse_s(&[b'T', 7, 0x34, 0x12]), Ok((7, 0x1234)));
+//!
+//! let e = p.parse_s(&[b'T', 7, 0x34]).unwrap_err();
+//! assert_eq!(e.index, Some(2));
+//! ```
+use super::*;
+use std::convert::TryInto;
+
+impl SItem for u8 {
+    fn step_lc(&self, l: usize, c: usize) -> (usize, usize) {
+        match self {
+            b'\n' => (l + 1, 0),
+            _ => (l, c + 1),
+        }
+    }
+
+    /// The valid utf8 in the first few bytes of the slice, errors only show 10 chars
+    fn found(s: &[u8]) -> &str {
+        let s = &s[..s.len().min(16)];
+        match std::str::from_utf8(s) {
+            Ok(v) => v,
+            Err(e) => std::str::from_utf8(&s[..e.valid_up_to()]).unwrap_or(""),
+        }
+    }
+}
+
+pub type BIter<'a> = SIter<'a, u8>;
+pub type BParseRes<'a, V> = SParseRes<'a, u8, V>;
+
+/// Parsers working on bytes
+pub trait BParser<'a>: SParser<'a, u8> {}
+impl<'a, P: SParser<'a, u8>> BParser<'a> for P {}
+
+impl<'a> SParser<'a, u8> for u8 {
+    type Out = u8;
+    fn parse(&self, i: &BIter<'a>) -> BParseRes<'a, u8> {
+        let mut it = *i;
+        match it.next() {
+            Some(b) if b == self => Ok((it, *b, None)),
+            _ => i.err_r(Expected::Byte(*self)),
+        }
+    }
+}
+
+impl<'a> SParser<'a, u8> for &'static [u8] {
+    type Out = &'static [u8];
+    fn parse(&self, i: &BIter<'a>) -> BParseRes<'a, Self::Out> {
+        match i.as_slice().starts_with(self) {
+            true => {
+                let mut it = *i;
+                for _ in 0..self.len() {
+                    it.next();
+                }
+                Ok((it, self, None))
+            }
+            false => i.err_r(Expected::Bytes(self)),
+        }
+    }
+}
+
+impl<'a, const N: usize> SParser<'a, u8> for &'static [u8; N] {
+    type Out = &'static [u8];
+    fn parse(&self, i: &BIter<'a>) -> BParseRes<'a, Self::Out> {
+        (&self[..]).parse(i)
+    }
+}
+
+pub trait ByteBool: Sized {
+    fn byte_bool(&self, b: u8) -> bool;
+
+    fn expected(&self) -> Expected {
+        Expected::Str(std::any::type_name::<Self>())
+    }
+
+    fn one(self) -> OneByte<Self> {
+        OneByte { bb: self }
+    }
+
+    fn star(self) -> ByteRange<Self> {
+        ByteRange {
+            bb: self,
+            min: 0,
+            exact: false,
+        }
+    }
+
+    fn plus(self) -> ByteRange<Self> {
+        ByteRange {
+            bb: self,
+            min: 1,
+            exact: false,
+        }
+    }
+
+    /// min_n not min to avoid ambiguity with std::cmp::Ord
+    fn min_n(self, min: usize) -> ByteRange<Self> {
+        ByteRange {
+            bb: self,
+            min,
+            exact: false,
+        }
+    }
+
+    fn exact(self, n: usize) -> ByteRange<Self> {
+        ByteRange {
+            bb: self,
+            min: n,
+            exact: true,
+        }
+    }
+}
+
+impl ByteBool for u8 {
+    fn byte_bool(&self, b: u8) -> bool {
+        *self == b
+    }
+    fn expected(&self) -> Expected {
+        Expected::Byte(*self)
+    }
+}
+
+impl ByteBool for std::ops::RangeInclusive<u8> {
+    fn byte_bool(&self, b: u8) -> bool {
+        self.contains(&b)
+    }
+}
+
+impl<F: Fn(u8) -> bool> ByteBool for F {
+    fn byte_bool(&self, b: u8) -> bool {
+        (self)(b)
+    }
+}
+
+impl<A: ByteBool, B: ByteBool> ByteBool for (A, B) {
+    fn byte_bool(&self, b: u8) -> bool {
+        self.0.byte_bool(b) || self.1.byte_bool(b)
+    }
+    fn expected(&self) -> Expected {
+        Expected::OneOf(vec![self.0.expected(), self.1.expected()])
+    }
+}
+
+impl<A: ByteBool, B: ByteBool, C: ByteBool> ByteBool for (A, B, C) {
+    fn byte_bool(&self, b: u8) -> bool {
+        self.0.byte_bool(b) || self.1.byte_bool(b) || self.2.byte_bool(b)
+    }
+    fn expected(&self) -> Expected {
+        Expected::OneOf(vec![
+            self.0.expected(),
+            self.1.expected(),
+            self.2.expected(),
+        ])
+    }
+}
+
+macro_rules! byte_bool {
+    ($id:ident,$x:expr) => {
+        #[derive(Copy, Clone)]
+        pub struct $id;
+        impl ByteBool for $id {
+            fn byte_bool(&self, b: u8) -> bool {
+                ($x)(b)
+            }
+            fn expected(&self) -> Expected {
+                Expected::CharIn(stringify!($id))
+            }
+        }
+    };
+}
+
+byte_bool!(AnyByte, |_| true);
+byte_bool!(NumByte, |b: u8| b.is_ascii_digit());
+byte_bool!(AlphaByte, |b: u8| b.is_ascii_alphabetic());
+byte_bool!(HexByte, |b: u8| b.is_ascii_hexdigit());
+byte_bool!(WSByte, |b: u8| b == b' ' || b == b'\t');
+
+pub struct OneByte<B> {
+    bb: B,
+}
+
+impl<'a, B: ByteBool> SParser<'a, u8> for OneByte<B> {
+    type Out = u8;
+    fn parse(&self, i: &BIter<'a>) -> BParseRes<'a, u8> {
+        let mut it = *i;
+        match it.next() {
+            Some(b) if self.bb.byte_bool(*b) => Ok((it, *b, None)),
+            _ => i.err_r(self.bb.expected()),
+        }
+    }
+}
+
+pub struct ByteRange<B> {
+    bb: B,
+    min: usize,
+    exact: bool,
+}
+
+impl<'a, B: ByteBool> SParser<'a, u8> for ByteRange<B> {
+    type Out = &'a [u8];
+    fn parse(&self, i: &BIter<'a>) -> BParseRes<'a, &'a [u8]> {
+        let mut it = *i;
+        let mut done = 0;
+        loop {
+            if done == self.min && self.exact {
+                return Ok((it, i.slice_to(&it), None));
+            }
+            let it2 = it;
+            match it.next() {
+                Some(b) if self.bb.byte_bool(*b) => done += 1,
+                _ => {
+                    if done >= self.min {
+                        let eo = Some(it2.err(self.bb.expected()));
+                        return Ok((it2, i.slice_to(&it2), eo));
+                    }
+                    return it2.err_r(self.bb.expected());
+                }
+            }
+        }
+    }
+}
+
+pub struct Take {
+    n: usize,
+}
+
+impl<'a> SParser<'a, u8> for Take {
+    type Out = &'a [u8];
+    fn parse(&self, i: &BIter<'a>) -> BParseRes<'a, &'a [u8]> {
+        AnyByte.exact(self.n).parse(i)
+    }
+}
+
+/// Takes exactly n bytes of any value
+pub fn take(n: usize) -> Take {
+    Take { n }
+}
+
+macro_rules! num_parser {
+    ($name:ident,$t:ty,$from:ident) => {
+        pub fn $name<'a>(i: &BIter<'a>) -> BParseRes<'a, $t> {
+            let exp = || i.err(Expected::Str(stringify!($name)));
+            let (it, b, _) = take(std::mem::size_of::<$t>())
+                .parse(i)
+                .map_err(|_| exp())?;
+            match b.try_into() {
+                Ok(arr) => Ok((it, <$t>::$from(arr), None)),
+                Err(_) => Err(exp()),
+            }
+        }
+    };
+}
+
+num_parser!(be_u16, u16, from_be_bytes);
+num_parser!(le_u16, u16, from_le_bytes);
+num_parser!(be_u32, u32, from_be_bytes);
+num_parser!(le_u32, u32, from_le_bytes);
+num_parser!(be_u64, u64, from_be_bytes);
+num_parser!(le_u64, u64, from_le_bytes);
+num_parser!(be_i16, i16, from_be_bytes);
+num_parser!(le_i16, i16, from_le_bytes);
+num_parser!(be_i32, i32, from_be_bytes);
+num_parser!(le_i32, i32, from_le_bytes);
+
+/// Parses bytes with `p` then requires them to be valid utf8
+///
+/// ```rust
+/// use bogobble::slice::{bytes::*, SParser};
+/// let p = (utf8(AlphaByte.plus()), b':', be_u16);
+/// assert_eq!(p.parse_s(b"len:\x01\x00"), Ok(("len", b':', 256)));
+/// ```
+pub fn utf8<'a, P: SParser<'a, u8, Out = &'a [u8]>>(p: P) -> Utf8<P> {
+    Utf8 { p }
+}
+
+pub struct Utf8<P> {
+    p: P,
+}
+
+impl<'a, P: SParser<'a, u8, Out = &'a [u8]>> SParser<'a, u8> for Utf8<P> {
+    type Out = &'a str;
+    fn parse(&self, i: &BIter<'a>) -> BParseRes<'a, &'a str> {
+        let (it, b, e) = self.p.parse(i)?;
+        match std::str::from_utf8(b) {
+            Ok(s) => Ok((it, s, e)),
+            Err(_) => i.err_r(Expected::Str("Valid UTF-8")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::slice;
+
+    #[test]
+    fn test_bytes_combine() {
+        let p = slice::sep_plus(slice::or(b"ab".ig(), NumByte.plus().ig()), b',');
+        assert_eq!(p.parse_s(b"ab,12,ab").map(|v| v.len()), Ok(3));
+        let e = p.parse_s(b"x").unwrap_err();
+        assert_eq!(e.index, Some(0));
+        assert_eq!(
+            e.exp,
+            Expected::OneOf(vec![Expected::Bytes(b"ab"), Expected::CharIn("NumByte")])
+        );
+    }
+
+    #[test]
+    fn test_bytes_err_reports_offset() {
+        let p = (b"\x00\x01", slice::star(0xffu8), be_u32);
+        let e = p.parse_s(b"\x00\x01\xff\xffab").unwrap_err();
+        assert_eq!(e.index, Some(4));
+        assert_eq!(e.found, "ab");
+        let (_, ffs, n) = p.parse_s(b"\x00\x01\xff\x00\x00\x01\x02").unwrap();
+        assert_eq!(ffs, vec![0xff]);
+        assert_eq!(n, 258);
+    }
+
+    #[test]
+    fn test_found_reads_a_prefix() {
+        let mut long = b"hello world, ".repeat(1000);
+        assert_eq!(u8::found(&long), "hello world, hel");
+        long[3] = 0xff;
+        assert_eq!(u8::found(&long), "hel");
+        // a char cut at the end of the prefix is left out
+        assert_eq!(u8::found("aaaaaaaaaaaaaaaé".as_bytes()), "aaaaaaaaaaaaaaa");
+    }
+}
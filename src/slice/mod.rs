@@ -0,0 +1,587 @@
+//! Parsers that work on slices of items instead of a `&str`.
+//!
+//! The main uses are parsing bytes, (see [bytes]) so that binary and mixed binary/text formats
+//! can be handled with the same combinators as the text parsers, and parsing the output of a
+//! lexer (see [token]). Errors are still [PErr]s, with `index` holding the offset of the failing
+//! item, or for tokens the offset in the source text.
+//!
+//! The combinators `Or`, `Maybe`, `Star`, `Plus`, `SepStar`, `SepPlus`, `SepUntil`, `Map`,
+//! `TryMap` and the tuples all work as [SParser]s. The free functions here build them, as the
+//! ones in the crate root require a text [Parser].
+//!
+//! ```rust
+//! use bogobble::slice::{self, bytes::*, SParser};
+//! let p = (b"GIF", slice::sep_plus(NumByte.plus(), b','), slice::maybe(b';'));
+//! let (_, v, e) = p.parse_s(b"GIF12,3,45;").unwrap();
+//! assert_eq!(v, vec![&b"12"[..], b"3", b"45"]);
+//! assert_eq!(e, Some(b';'));
+//!
+//! let e = p.parse_s(b"GIF12,").unwrap_err();
+//! assert_eq!(e.index, None);
+//! let e = p.parse_s(b"GIF12,x").unwrap_err();
+//! assert_eq!(e.index, Some(6));
+//! ```
+pub mod bytes;
+pub mod token;
+
+use crate::combi::Maybe;
+use crate::convert::*;
+use crate::err::*;
+use crate::parser::{Break, BRP};
+use crate::repeater::{Exact, Plus, SepPlus, SepStar, SepUntil, Star};
+use crate::select::{Ig, Or};
+
+pub type SParseRes<'a, T, V> = Result<(SIter<'a, T>, V, Option<PErr<'a>>), PErr<'a>>;
+
+/// Anything that can be held in a slice and parsed by an [SParser]
+pub trait SItem: Sized {
+    /// The line and column after this item, given those before it.
+    fn step_lc(&self, l: usize, c: usize) -> (usize, usize);
+
+    /// The text shown as "found" in errors at the start of this slice.
+    fn found(s: &[Self]) -> &str;
+
+    /// The index, line and column reported by errors at this item, given its position in
+    /// the slice and the counted line and column.
+    fn err_at(&self, pos: usize, l: usize, c: usize) -> (usize, usize, usize) {
+        (pos, l, c)
+    }
+
+    /// The index where the text covered by an error at this item finishes
+    fn err_end(&self, pos: usize) -> usize {
+        pos + 1
+    }
+}
+
+pub struct SIter<'a, T> {
+    orig: &'a [T],
+    pos: usize,
+    l: usize,
+    c: usize,
+}
+
+impl<'a, T> Clone for SIter<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, T> Copy for SIter<'a, T> {}
+
+impl<'a, T: std::fmt::Debug> std::fmt::Debug for SIter<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "SIter{{pos:{},l:{},c:{}}}", self.pos, self.l, self.c)
+    }
+}
+
+impl<'a, T: SItem> SIter<'a, T> {
+    pub fn new(s: &'a [T]) -> Self {
+        SIter {
+            orig: s,
+            pos: 0,
+            l: 0,
+            c: 0,
+        }
+    }
+
+    pub fn orig_slice(&self) -> &'a [T] {
+        self.orig
+    }
+
+    pub fn as_slice(&self) -> &'a [T] {
+        &self.orig[self.pos..]
+    }
+
+    /// The items between this and a later iterator
+    pub fn slice_to(&self, fin: &Self) -> &'a [T] {
+        &self.orig[self.pos..fin.pos]
+    }
+
+    pub fn err(&self, exp: Expected) -> PErr<'a> {
+        let (index, end, line, col) = match self.as_slice().first() {
+            Some(t) => {
+                let (i, l, c) = t.err_at(self.pos, self.l, self.c);
+                (Some(i), Some(t.err_end(self.pos)), l, c)
+            }
+            None => (None, None, self.l, self.c),
+        };
+        PErr {
+            exp,
+            found: T::found(self.as_slice()),
+            index,
+            end,
+            line,
+            col,
+            is_break: false,
+            is_cut: false,
+            child: None,
+            file: None,
+            context: None,
+        }
+    }
+
+    pub fn err_s(&self, s: &'static str) -> PErr<'a> {
+        self.err(Expected::Str(s))
+    }
+
+    pub fn err_r<V>(&self, e: Expected) -> Result<V, PErr<'a>> {
+        Err(self.err(e))
+    }
+
+    pub fn lc(&self) -> (usize, usize) {
+        (self.l, self.c)
+    }
+
+    /// The offset of the next item, None at the end of input to match [crate::PIter::index]
+    pub fn index(&self) -> Option<usize> {
+        match self.pos < self.orig.len() {
+            true => Some(self.pos),
+            false => None,
+        }
+    }
+
+    /// The offset of the next item, or the length of the slice at the end.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn eoi(&self) -> bool {
+        self.pos >= self.orig.len()
+    }
+}
+
+impl<'a, T: SItem> Iterator for SIter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        let r = self.orig.get(self.pos)?;
+        let (l, c) = r.step_lc(self.l, self.c);
+        self.pos += 1;
+        self.l = l;
+        self.c = c;
+        Some(r)
+    }
+}
+
+pub trait SParser<'a, T: SItem>: Sized {
+    type Out;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out>;
+
+    fn parse_s(&self, s: &'a [T]) -> Result<Self::Out, PErr<'a>> {
+        self.parse(&SIter::new(s)).map(|(_, v, _)| v)
+    }
+
+    fn or<B: SParser<'a, T, Out = Self::Out>>(self, b: B) -> Or<Self, B> {
+        Or { a: self, b }
+    }
+
+    ///Borrowed Parser
+    fn br(&self) -> BRP<'_, Self> {
+        BRP(self)
+    }
+
+    fn asv<V: Clone>(self, v: V) -> Asv<Self, V> {
+        Asv { a: self, v }
+    }
+
+    fn map<B, F: Fn(Self::Out) -> B>(self, f: F) -> Map<Self, F> {
+        Map { a: self, f }
+    }
+
+    fn try_map<B, F: Fn(Self::Out) -> Result<B, Expected>>(self, f: F) -> TryMap<Self, F> {
+        TryMap { a: self, f }
+    }
+
+    fn ig(self) -> Ig<Self> {
+        Ig { a: self }
+    }
+
+    fn brk(self) -> Break<Self> {
+        Break { a: self }
+    }
+}
+
+impl<'a, T: SItem + 'a, F, V> SParser<'a, T> for F
+where
+    F: Fn(&SIter<'a, T>) -> SParseRes<'a, T, V>,
+{
+    type Out = V;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, V> {
+        self(it)
+    }
+}
+
+impl<'a, 'b, T: SItem, P: SParser<'a, T>> SParser<'a, T> for BRP<'b, P> {
+    type Out = P::Out;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        self.0.parse(it)
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>> SParser<'a, T> for Break<A> {
+    type Out = A::Out;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, A::Out> {
+        self.a.parse(it).map_err(|e| e.brk())
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>> SParser<'a, T> for Ig<A> {
+    type Out = ();
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, ()> {
+        self.a.parse(it).map(|(i, _, e)| (i, (), e))
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>, V: Clone> SParser<'a, T> for Asv<A, V> {
+    type Out = V;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, V> {
+        self.a.parse(it).map(|(i, _, e)| (i, self.v.clone(), e))
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>, B, F: Fn(A::Out) -> B> SParser<'a, T> for Map<A, F> {
+    type Out = B;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, B> {
+        let (ri, v, ex) = self.a.parse(it)?;
+        Ok((ri, (self.f)(v), ex))
+    }
+}
+
+impl<'a, T, A, B, F> SParser<'a, T> for TryMap<A, F>
+where
+    T: SItem,
+    A: SParser<'a, T>,
+    F: Fn(A::Out) -> Result<B, Expected>,
+{
+    type Out = B;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, B> {
+        let (ri, v, ct) = self.a.parse(it)?;
+        match (self.f)(v) {
+            Ok(v2) => Ok((ri, v2, ct)),
+            Err(e) => ri.err_r(e),
+        }
+    }
+}
+
+impl<'a, T, A, B, V> SParser<'a, T> for Or<A, B>
+where
+    T: SItem,
+    A: SParser<'a, T, Out = V>,
+    B: SParser<'a, T, Out = V>,
+{
+    type Out = V;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, V> {
+        match self.a.parse(it) {
+            Ok(r) => Ok(r),
+            Err(e) if e.is_break => Err(e),
+            Err(e) => match self.b.parse(it) {
+                Ok(r) => Ok(r),
+                Err(e2) if e2.is_break => Err(e2),
+                Err(e2) => Err(e.longer(e2)),
+            },
+        }
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>> SParser<'a, T> for Maybe<A> {
+    type Out = Option<A::Out>;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        match self.0.parse(it) {
+            Ok((ir, v, ex)) => Ok((ir, Some(v), ex)),
+            Err(e) => Ok((*it, None, Some(e))),
+        }
+    }
+}
+
+fn join_op<'a>(e: PErr<'a>, op: Option<PErr<'a>>) -> PErr<'a> {
+    match op {
+        Some(e2) => e.join(e2),
+        None => e,
+    }
+}
+
+impl<'a, T, A, B> SParser<'a, T> for (A, B)
+where
+    T: SItem,
+    A: SParser<'a, T>,
+    B: SParser<'a, T>,
+{
+    type Out = (A::Out, B::Out);
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        let (it2, av, c1) = self.0.parse(it)?;
+        let (it3, bv, c2) = self.1.parse(&it2).map_err(|e| join_op(e, c1))?;
+        Ok((it3, (av, bv), c2))
+    }
+}
+
+impl<'a, T, A, B, C> SParser<'a, T> for (A, B, C)
+where
+    T: SItem,
+    A: SParser<'a, T>,
+    B: SParser<'a, T>,
+    C: SParser<'a, T>,
+{
+    type Out = (A::Out, B::Out, C::Out);
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        ((self.0.br(), self.1.br()), self.2.br())
+            .parse(it)
+            .map(|(i, ((a, b), c), e)| (i, (a, b, c), e))
+    }
+}
+
+impl<'a, T, A, B, C, D> SParser<'a, T> for (A, B, C, D)
+where
+    T: SItem,
+    A: SParser<'a, T>,
+    B: SParser<'a, T>,
+    C: SParser<'a, T>,
+    D: SParser<'a, T>,
+{
+    type Out = (A::Out, B::Out, C::Out, D::Out);
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        ((self.0.br(), self.1.br()), (self.2.br(), self.3.br()))
+            .parse(it)
+            .map(|(i, ((a, b), (c, d)), e)| (i, (a, b, c, d), e))
+    }
+}
+
+impl<'a, T, A, B, C, D, E> SParser<'a, T> for (A, B, C, D, E)
+where
+    T: SItem,
+    A: SParser<'a, T>,
+    B: SParser<'a, T>,
+    C: SParser<'a, T>,
+    D: SParser<'a, T>,
+    E: SParser<'a, T>,
+{
+    type Out = (A::Out, B::Out, C::Out, D::Out, E::Out);
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        (
+            (self.0.br(), self.1.br(), self.2.br()),
+            (self.3.br(), self.4.br()),
+        )
+            .parse(it)
+            .map(|(i, ((a, b, c), (d, e)), ex)| (i, (a, b, c, d, e), ex))
+    }
+}
+
+impl<'a, T, A, B, C, D, E, F> SParser<'a, T> for (A, B, C, D, E, F)
+where
+    T: SItem,
+    A: SParser<'a, T>,
+    B: SParser<'a, T>,
+    C: SParser<'a, T>,
+    D: SParser<'a, T>,
+    E: SParser<'a, T>,
+    F: SParser<'a, T>,
+{
+    type Out = (A::Out, B::Out, C::Out, D::Out, E::Out, F::Out);
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        (
+            (self.0.br(), self.1.br(), self.2.br()),
+            (self.3.br(), self.4.br(), self.5.br()),
+        )
+            .parse(it)
+            .map(|(i, ((a, b, c), (d, e, f)), ex)| (i, (a, b, c, d, e, f), ex))
+    }
+}
+
+pub fn do_rep<'a, T: SItem, A: SParser<'a, T>>(
+    i: &SIter<'a, T>,
+    a: &A,
+    min: usize,
+    exact: bool,
+) -> SParseRes<'a, T, Vec<A::Out>> {
+    let mut it = *i;
+    let mut res = Vec::new();
+    if exact && min == 0 {
+        return Ok((it, res, None));
+    }
+    loop {
+        match a.parse(&it) {
+            Ok((i2, v, _)) => {
+                res.push(v);
+                if it.pos() == i2.pos() && !exact {
+                    return Err(it.err_s("To Consume some data"));
+                }
+                if res.len() == min && exact {
+                    return Ok((i2, res, None));
+                }
+                it = i2;
+            }
+            Err(e) => {
+                if res.len() >= min && !exact {
+                    return Ok((it, res, Some(e)));
+                }
+                return Err(e);
+            }
+        }
+    }
+}
+
+fn do_sep<'a, T: SItem, A: SParser<'a, T>, B: SParser<'a, T>>(
+    i: &SIter<'a, T>,
+    a: &A,
+    b: &B,
+    min: usize,
+) -> SParseRes<'a, T, Vec<A::Out>> {
+    let mut res = Vec::new();
+    let mut ri = *i;
+    loop {
+        ri = match a.parse(&ri) {
+            Ok((r, v, _)) => {
+                res.push(v);
+                r
+            }
+            Err(e) => {
+                if res.is_empty() && min == 0 {
+                    return Ok((ri, res, Some(e)));
+                }
+                return Err(e);
+            }
+        };
+        ri = match b.parse(&ri) {
+            Ok((r, _, _)) => r,
+            Err(e) => {
+                if res.len() < min {
+                    return Err(e);
+                }
+                return Ok((ri, res, Some(e)));
+            }
+        };
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>> SParser<'a, T> for Star<A> {
+    type Out = Vec<A::Out>;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        do_rep(it, &self.0, 0, false)
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>> SParser<'a, T> for Plus<A> {
+    type Out = Vec<A::Out>;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        do_rep(it, &self.0, 1, false)
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>> SParser<'a, T> for Exact<A> {
+    type Out = Vec<A::Out>;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        do_rep(it, &self.a, self.n, true)
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>, B: SParser<'a, T>> SParser<'a, T> for SepStar<A, B> {
+    type Out = Vec<A::Out>;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        do_sep(it, &self.a, &self.b, 0)
+    }
+}
+
+impl<'a, T: SItem, A: SParser<'a, T>, B: SParser<'a, T>> SParser<'a, T> for SepPlus<A, B> {
+    type Out = Vec<A::Out>;
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        do_sep(it, &self.a, &self.b, 1)
+    }
+}
+
+impl<'a, T, A, B, C> SParser<'a, T> for SepUntil<A, B, C>
+where
+    T: SItem,
+    A: SParser<'a, T>,
+    B: SParser<'a, T>,
+    C: SParser<'a, T>,
+{
+    type Out = (Vec<A::Out>, C::Out);
+    fn parse(&self, i: &SIter<'a, T>) -> SParseRes<'a, T, Self::Out> {
+        let mut ri = *i;
+        let mut res = Vec::new();
+        if let Ok((r, v, _)) = self.c.parse(&ri) {
+            return Ok((r, (res, v), None));
+        }
+        loop {
+            ri = match self.a.parse(&ri) {
+                Ok((r, v, _)) => {
+                    res.push(v);
+                    r
+                }
+                Err(e) => return Err(e),
+            };
+            let c_err = match self.c.parse(&ri) {
+                Ok((r, v, _)) => return Ok((r, (res, v), None)),
+                Err(e) => e,
+            };
+            ri = match self.b.parse(&ri) {
+                Ok((r, _, _)) => r,
+                Err(e) => return Err(e.join(c_err)),
+            }
+        }
+    }
+}
+
+pub fn or<'a, T: SItem, A, B, V>(a: A, b: B) -> Or<A, B>
+where
+    A: SParser<'a, T, Out = V>,
+    B: SParser<'a, T, Out = V>,
+{
+    Or { a, b }
+}
+
+pub fn maybe<'a, T: SItem, A: SParser<'a, T>>(a: A) -> Maybe<A> {
+    Maybe(a)
+}
+
+pub fn star<'a, T: SItem, A: SParser<'a, T>>(a: A) -> Star<A> {
+    Star(a)
+}
+
+pub fn plus<'a, T: SItem, A: SParser<'a, T>>(a: A) -> Plus<A> {
+    Plus(a)
+}
+
+pub fn exact<'a, T: SItem, A: SParser<'a, T>>(a: A, n: usize) -> Exact<A> {
+    Exact { a, n }
+}
+
+pub fn sep_star<'a, T: SItem, A: SParser<'a, T>, B: SParser<'a, T>>(a: A, b: B) -> SepStar<A, B> {
+    SepStar { a, b }
+}
+
+pub fn sep_plus<'a, T: SItem, A: SParser<'a, T>, B: SParser<'a, T>>(a: A, b: B) -> SepPlus<A, B> {
+    SepPlus { a, b }
+}
+
+pub fn sep_until<'a, T, A, B, C>(a: A, b: B, c: C) -> SepUntil<A, B, C>
+where
+    T: SItem,
+    A: SParser<'a, T>,
+    B: SParser<'a, T>,
+    C: SParser<'a, T>,
+{
+    SepUntil { a, b, c }
+}
+
+pub struct SliceRange<P> {
+    p: P,
+}
+
+impl<'a, T: SItem + 'a, P: SParser<'a, T>> SParser<'a, T> for SliceRange<P> {
+    type Out = &'a [T];
+    fn parse(&self, it: &SIter<'a, T>) -> SParseRes<'a, T, &'a [T]> {
+        let (ri, _, e) = self.p.parse(it)?;
+        Ok((ri, it.slice_to(&ri), e))
+    }
+}
+
+/// Returns the items consumed by the parser instead of it's result,
+/// the slice equivalent of [crate::str_range]
+pub fn slice_range<'a, T: SItem, P: SParser<'a, T>>(p: P) -> SliceRange<P> {
+    SliceRange { p }
+}
+
+pub fn eoi<'a, T: SItem>(it: &SIter<'a, T>) -> SParseRes<'a, T, ()> {
+    match it.eoi() {
+        true => Ok((*it, (), None)),
+        false => it.err_r(Expected::EOI),
+    }
+}
@@ -0,0 +1,195 @@
+//! Parsing a slice of tokens produced by a lexer.
+//!
+//! A [Token] keeps the span of source text it came from, so errors while parsing the tokens
+//! point at the failing token's offset, line and column in the source.
+//!
+//! ```rust
+//! use bogobble::slice::{self, token::*, SParser};
+//! use bogobble::*;
+//!
+//! // A tiny lexer from the normal text parsers
+//! let lex = star(wn_((index, line_col, or!(
+//!     string(Alpha.plus()).map(|s| ("id", s)),
+//!     string(NumDigit.plus()).map(|s| ("num", s)),
+//!     string(Any.one()).map(|s| ("punct", s)),
+//! ))));
+//! let src = "f(a, 12,\n  b) c";
+//! let toks: Vec<_> = lex
+//!     .parse_s(src)
+//!     .unwrap()
+//!     .into_iter()
+//!     .map(|(i, (line, col), (kind, s))| {
+//!         let start = i.unwrap();
+//!         Token::new(kind, &src[start..start + s.len()], start, line, col)
+//!     })
+//!     .collect();
+//!
+//! let arg = slice::or(kind("id"), kind("num")).map(|t| t.text);
+//! let args = slice::sep_until(arg, kind_text("punct", ","), kind_text("punct", ")"));
+//! let call = (kind("id"), kind_text("punct", "("), args);
+//! let (f, _, (args, _)) = call.parse_s(&toks).unwrap();
+//! assert_eq!(f.text, "f");
+//! assert_eq!(args, vec!["a", "12", "b"]);
+//!
+//! let e = (call, slice::eoi).parse_s(&toks).unwrap_err();
+//! assert_eq!((e.index, e.line, e.col, e.found), (Some(14), 1, 5, "c"));
+//! ```
+use super::*;
+use crate::iter::LcConf;
+use std::fmt::Debug;
+
+/// The kind of a token, with what to report when a different kind was found
+pub trait TokenKind: Copy + PartialEq + Debug {
+    fn expected(&self) -> Expected;
+}
+
+impl TokenKind for &'static str {
+    fn expected(&self) -> Expected {
+        Expected::Str(self)
+    }
+}
+
+impl TokenKind for char {
+    fn expected(&self) -> Expected {
+        Expected::Char(*self)
+    }
+}
+
+/// A lexed token, with its position in the source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a, K> {
+    pub kind: K,
+    pub text: &'a str,
+    pub start: usize,
+    pub fin: usize,
+    pub line: usize,
+    pub col: usize,
+    /// The line and column after the token's text
+    pub end_line: usize,
+    pub end_col: usize,
+}
+
+impl<'a, K> Token<'a, K> {
+    /// The end line and column are counted with the default [LcConf], if the text was
+    /// counted differently, set them with [Token::with_end_lc]
+    pub fn new(kind: K, text: &'a str, start: usize, line: usize, col: usize) -> Self {
+        let conf = LcConf::default();
+        let mut chars = text.chars().peekable();
+        let mut end = (line, col);
+        while let Some(c) = chars.next() {
+            end = conf.step(end, c, chars.peek().copied());
+        }
+        Token {
+            kind,
+            text,
+            start,
+            fin: start + text.len(),
+            line,
+            col,
+            end_line: end.0,
+            end_col: end.1,
+        }
+    }
+
+    pub fn with_end_lc(mut self, (end_line, end_col): (usize, usize)) -> Self {
+        self.end_line = end_line;
+        self.end_col = end_col;
+        self
+    }
+}
+
+impl<'a, K> SItem for Token<'a, K> {
+    fn step_lc(&self, _: usize, _: usize) -> (usize, usize) {
+        (self.end_line, self.end_col)
+    }
+
+    fn found(s: &[Self]) -> &str {
+        s.first().map(|t| t.text).unwrap_or("")
+    }
+
+    fn err_at(&self, _: usize, _: usize, _: usize) -> (usize, usize, usize) {
+        (self.start, self.line, self.col)
+    }
+
+    fn err_end(&self, _: usize) -> usize {
+        self.fin
+    }
+}
+
+pub type TIter<'a, K> = SIter<'a, Token<'a, K>>;
+pub type TParseRes<'a, K, V> = SParseRes<'a, Token<'a, K>, V>;
+
+pub struct Kind<K> {
+    k: K,
+}
+
+impl<'a, K: TokenKind + 'a> SParser<'a, Token<'a, K>> for Kind<K> {
+    type Out = &'a Token<'a, K>;
+    fn parse(&self, i: &TIter<'a, K>) -> TParseRes<'a, K, Self::Out> {
+        let mut it = *i;
+        match it.next() {
+            Some(t) if t.kind == self.k => Ok((it, t, None)),
+            _ => i.err_r(self.k.expected()),
+        }
+    }
+}
+
+/// Matches one token of the kind, returning the token
+pub fn kind<K: TokenKind>(k: K) -> Kind<K> {
+    Kind { k }
+}
+
+pub struct KindText<K> {
+    k: K,
+    s: &'static str,
+}
+
+impl<'a, K: TokenKind + 'a> SParser<'a, Token<'a, K>> for KindText<K> {
+    type Out = &'a Token<'a, K>;
+    fn parse(&self, i: &TIter<'a, K>) -> TParseRes<'a, K, Self::Out> {
+        let mut it = *i;
+        match it.next() {
+            Some(t) if t.kind == self.k && t.text == self.s => Ok((it, t, None)),
+            _ => i.err_r(Expected::Str(self.s)),
+        }
+    }
+}
+
+/// Matches one token of the kind with exactly the text given, for keywords and punctuation
+pub fn kind_text<K: TokenKind>(k: K, s: &'static str) -> KindText<K> {
+    KindText { k, s }
+}
+
+/// Matches any one token
+pub fn any_token<'a, K: 'a>(i: &TIter<'a, K>) -> TParseRes<'a, K, &'a Token<'a, K>> {
+    let mut it = *i;
+    match it.next() {
+        Some(t) => Ok((it, t, None)),
+        None => i.err_r(Expected::Str("a token")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_token_errors_use_spans() {
+        let toks = [
+            Token::new('a', "a", 0, 0, 0),
+            Token::new('+', "+", 2, 0, 2),
+            Token::new('a', "b\nc", 6, 1, 3),
+        ];
+        let p = crate::slice::sep_plus(kind('a'), kind('+'));
+        assert_eq!(p.parse_s(&toks).unwrap().len(), 2);
+
+        let e = (kind('a'), kind('+'), kind('+'))
+            .parse_s(&toks)
+            .unwrap_err();
+        assert_eq!(e.exp, Expected::Char('+'));
+        assert_eq!((e.index, e.line, e.col, e.found), (Some(6), 1, 3, "b\nc"));
+
+        let e = (p, kind('+')).parse_s(&toks).unwrap_err();
+        assert_eq!((e.index, e.line, e.col), (None, 2, 1));
+    }
+}
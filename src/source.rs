@@ -0,0 +1,130 @@
+//! Keeping track of which source text errors and positions came from.
+//!
+//! Each file added to a [SourceMap] gets a [FileId]. Iterators made from the map carry that id
+//! into every [PErr], [Pos] and [PosTree] they produce, so the map can print them as
+//! `path:line:col` and safely get back the text they cover.
+//!
+//! ```rust
+//! use bogobble::*;
+//! let mut sm = SourceMap::new();
+//! let main = sm.add("main.cfg", "a = 3\nb = 4");
+//! let inc = sm.add("inc.cfg", "c = 5\nd = ?");
+//!
+//! let p = star(wn_(pos_ig((Alpha.plus(), ws_('='), ws_(common::Int)))));
+//! let (_, v, _) = p.parse(&sm.iter(main)).unwrap();
+//! assert_eq!(sm.pos_str(&v[1]), Some("b = 4"));
+//! assert_eq!(sm.pos_string(&v[1]), "main.cfg:2:1");
+//!
+//! let e = (p, eoi).parse(&sm.iter(inc)).unwrap_err();
+//! assert!(sm.err_string(&e).starts_with("inc.cfg:2:5: "));
+//! ```
+use crate::err::*;
+use crate::iter::*;
+use crate::partial::PosTree;
+use crate::reader::*;
+use crate::span::Span;
+use std::convert::TryFrom;
+use std::num::NonZeroU32;
+
+/// The index of a file in a [SourceMap] + 1, non zero so `Option<FileId>` keeps [PErr] small
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(NonZeroU32);
+
+impl FileId {
+    fn from_index(n: usize) -> Self {
+        let id = u32::try_from(n + 1).ok().and_then(NonZeroU32::new);
+        FileId(id.expect("more files than a FileId can hold"))
+    }
+
+    /// The position of the file in its map, from 0
+    pub fn index(self) -> usize {
+        self.0.get() as usize - 1
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub name: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<N: Into<String>, T: Into<String>>(&mut self, name: N, text: T) -> FileId {
+        self.files.push(SourceFile {
+            name: name.into(),
+            text: text.into(),
+        });
+        FileId::from_index(self.files.len() - 1)
+    }
+
+    pub fn get(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get(id.index())
+    }
+
+    pub fn name(&self, id: FileId) -> Option<&str> {
+        self.get(id).map(|f| f.name.as_str())
+    }
+
+    pub fn text(&self, id: FileId) -> Option<&str> {
+        self.get(id).map(|f| f.text.as_str())
+    }
+
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|f| f.name == name)
+            .map(FileId::from_index)
+    }
+
+    /// An iterator over the file, that marks its errors and positions with the file's id.
+    ///
+    /// panics if the id did not come from this map.
+    pub fn iter(&self, id: FileId) -> PIter<'_> {
+        self.iter_conf(id, LcConf::default())
+    }
+
+    pub fn iter_conf(&self, id: FileId, conf: LcConf) -> PIter<'_> {
+        PIter::with_conf(&self.files[id.index()].text, conf).in_file(id)
+    }
+
+    /// Get the text a span covers in a file,
+    /// None if the file is unknown or the span does not fit the text
+    pub fn span_str(&self, id: FileId, span: Span) -> Option<&str> {
+        self.text(id)?.get(span.start..span.end)
+    }
+
+    pub fn pos_str<O>(&self, p: &Pos<O>) -> Option<&str> {
+        self.span_str(p.file?, p.span)
+    }
+
+    pub fn tree_str<I>(&self, t: &PosTree<I>) -> Option<&str> {
+        self.span_str(t.file?, t.span)
+    }
+
+    /// "path:line:col" with line and col counted from 1
+    pub fn lc_string(&self, file: Option<FileId>, line: usize, col: usize) -> String {
+        let name = file.and_then(|f| self.name(f)).unwrap_or("<unknown>");
+        format!("{}:{}:{}", name, line + 1, col + 1)
+    }
+
+    pub fn pos_string<O>(&self, p: &Pos<O>) -> String {
+        self.lc_string(p.file, p.line, p.col)
+    }
+
+    pub fn err_string(&self, e: &PErr) -> String {
+        format!("{}: {}", self.lc_string(e.file, e.line, e.col), e)
+    }
+
+    pub fn strung_string(&self, e: &StrungError) -> String {
+        format!("{}: {}", self.lc_string(e.file, e.line, e.col), e)
+    }
+}
@@ -0,0 +1,170 @@
+//! Byte ranges of the input, for locating results and errors.
+//!
+//! [Parser::spanned] pairs a result with the [Span] it was parsed from, and
+//! [Parser::map_with_span] passes the span and matched text to a constructor, so AST nodes can
+//! record where they came from. [PErr::span] gives the text an error covers.
+//!
+//! ```rust
+//! use bogobble::*;
+//! #[derive(Debug, PartialEq)]
+//! struct Var {
+//!     name: String,
+//!     span: Span,
+//! }
+//! let var = common::Ident.map_with_span(|name, span, _| Var { name, span });
+//! let p = (ws_(var), ws_('='), ws_(common::Int.spanned()));
+//! let (v, _, (n, nspan)) = p.parse_s("  abc = 42").unwrap();
+//! assert_eq!(v, Var { name: "abc".to_string(), span: Span::new(2, 5) });
+//! assert_eq!((n, nspan), (42, Span::new(8, 10)));
+//! assert_eq!(v.span.merge(nspan), Span::new(2, 10));
+//! assert_eq!(nspan.on_str("  abc = 42"), "42");
+//!
+//! let e = common::Ident.parse_s("é").unwrap_err();
+//! assert_eq!(e.span("é"), Some(Span::new(0, 2)));
+//! ```
+use crate::err::*;
+use crate::iter::*;
+use crate::lines::LineIndex;
+use crate::parser::*;
+
+/// A range of byte offsets, start inclusive, end exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// An empty span at the offset
+    pub fn at(offset: usize) -> Self {
+        Span::new(offset, offset)
+    }
+
+    /// From the optional offsets used by [PIter::index], where None is the end of s
+    pub fn from_opts(start: Option<usize>, end: Option<usize>, s: &str) -> Self {
+        Span::new(start.unwrap_or(s.len()), end.unwrap_or(s.len()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The smallest span covering both, including any gap between them
+    pub fn merge(self, b: Span) -> Span {
+        Span::new(self.start.min(b.start), self.end.max(b.end))
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
+    /// This assumes that this is the string it came from, out of range spans give ""
+    pub fn on_str<'a>(&self, s: &'a str) -> &'a str {
+        s.get(self.start..self.end).unwrap_or("")
+    }
+
+    /// The start and end (line, col) in s, for repeated lookups build a [LineIndex] instead
+    pub fn lc(&self, s: &str) -> ((usize, usize), (usize, usize)) {
+        LineIndex::new(s).span_lc(self)
+    }
+}
+
+impl<'a> PErr<'a> {
+    /// The text the error covers, given the string it came from
+    pub fn span(&self, s: &str) -> Option<Span> {
+        let start = self.index?;
+        Some(Span::new(start, self.end.unwrap_or(s.len())))
+    }
+}
+
+#[derive(Clone)]
+pub struct Spanned<P> {
+    p: P,
+}
+
+pub fn spanned<'a, P: Parser<'a>>(p: P) -> Spanned<P> {
+    Spanned { p }
+}
+
+impl<'a, P: Parser<'a>> Parser<'a> for Spanned<P> {
+    type Out = (P::Out, Span);
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
+        let (r, v, ex) = self.p.parse(it)?;
+        Ok((r, (v, Span::new(it.offset(), r.offset())), ex))
+    }
+}
+
+#[derive(Clone)]
+pub struct MapWithSpan<P, F> {
+    p: P,
+    f: F,
+}
+
+/// As [crate::map], f is also given the span and the text matched
+pub fn map_with_span<'a, P, B, F>(p: P, f: F) -> MapWithSpan<P, F>
+where
+    P: Parser<'a>,
+    F: Fn(P::Out, Span, &'a str) -> B,
+{
+    MapWithSpan { p, f }
+}
+
+impl<'a, P, B, F> Parser<'a> for MapWithSpan<P, F>
+where
+    P: Parser<'a>,
+    F: Fn(P::Out, Span, &'a str) -> B,
+{
+    type Out = B;
+    fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, B> {
+        let (r, v, ex) = self.p.parse(it)?;
+        let span = Span::new(it.offset(), r.offset());
+        Ok((r, (self.f)(v, span, it.str_to(r.index())), ex))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_span_ranges() {
+        let s = "ab\ncd";
+        let sp = Span::new(1, 4);
+        assert_eq!((sp.len(), sp.is_empty()), (3, false));
+        assert!(sp.contains(1) && sp.contains(3) && !sp.contains(4));
+        assert!(Span::at(2).is_empty());
+        assert_eq!(sp.lc(s), ((0, 1), (1, 1)));
+        assert_eq!(Span::from_opts(Some(3), None, s), Span::new(3, 5));
+        let p = pos_ig(Alpha.plus());
+        let v = last('\n', p).parse_s("\nhey").unwrap();
+        assert_eq!(v.span, Span::new(1, 4));
+        // errors at the end of the input cover nothing
+        let e = ('a', 'b').parse_s("a").unwrap_err();
+        assert_eq!(e.span("a"), None);
+    }
+
+    parser!((Greet->&'static str) or("hello", "help"));
+
+    #[test]
+    fn test_err_ends_cover_what_was_read() {
+        let e = "hello".parse_s("helxo").unwrap_err();
+        assert_eq!(e.span("helxo"), Some(Span::new(0, 4)));
+        // running out of input covers the rest
+        let e = "hello".parse_s("hel").unwrap_err();
+        assert_eq!(e.span("hel"), Some(Span::new(0, 3)));
+        // a rule failing where it started keeps how far its alternatives read
+        let e = Greet.parse_s("helxo").unwrap_err();
+        assert_eq!(e.span("helxo"), Some(Span::new(0, 4)));
+        assert_eq!(e.exp, Expected::Str("Greet"));
+        let e = ws_('a').parse_s("  b").unwrap_err();
+        assert_eq!(e.span("  b"), Some(Span::new(2, 3)));
+    }
+}
@@ -0,0 +1,457 @@
+//! Parsers that carry user state, for context sensitive grammars.
+//!
+//! An [StParser] is passed a `&mut S` alongside the iterator. When an alternative fails, `Or`,
+//! `Maybe`, the repeaters and the tuples put the state back how it was before they tried,
+//! so only changes made along the successful path remain. This means the state is cloned
+//! at each of those points, so it should be cheap to clone.
+//!
+//! Any [Parser] can be used as an StParser by wrapping it in [lift]. chars, strs and the
+//! structs made by [parser!] are StParsers already.
+//!
+//! ```rust
+//! use bogobble::*;
+//! use std::collections::BTreeSet;
+//!
+//! // C style : a typedef adds a type name, that can then start a declaration
+//! let type_name = st_try_map(lift(common::Ident), |s, types: &mut BTreeSet<String>| {
+//!     match types.contains(&s) {
+//!         true => Ok(s),
+//!         false => Err(Expected::Str("type name")),
+//!     }
+//! });
+//! let typedef = ("typedef ", lift(common::Ident), ';').st_map(|(_, s, _), types: &mut BTreeSet<String>| {
+//!     types.insert(s);
+//! });
+//! let decl = (type_name, ' ', lift(common::Ident), ';').st_map(|(t, _, n, _), _| (t, n));
+//! let p = st_sep_star(st_or(typedef.st_map(|_, _| None), decl.st_map(|d, _| Some(d))), '\n');
+//!
+//! let mut types = BTreeSet::new();
+//! let r = p.parse_st_s("typedef num;\nnum a;\nnum b;", &mut types).unwrap();
+//! assert_eq!(r[2], Some(("num".to_string(), "b".to_string())));
+//!
+//! // Failed alternatives do not leave anything behind
+//! let add = lift(common::Ident).st_map(|s, types: &mut BTreeSet<String>| { types.insert(s); });
+//! let p = st_or(("typedef ", add, '!').st_map(|_, _| ()), lift(Any.one()).st_map(|_, _| ()));
+//! let mut types = BTreeSet::new();
+//! p.parse_st_s("typedef x;", &mut types).unwrap();
+//! assert!(types.is_empty());
+//! ```
+use crate::combi::Maybe;
+use crate::err::*;
+use crate::iter::*;
+use crate::parser::*;
+use crate::repeater::{Plus, SepPlus, SepStar, Star};
+use crate::select::Or;
+
+pub trait StParser<'a, S>: Sized {
+    type Out;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, Self::Out>;
+
+    fn parse_st_s(&self, s: &'a str, st: &mut S) -> Result<Self::Out, PErr<'a>> {
+        self.parse_st(&PIter::new(s), st).map(|(_, v, _)| v)
+    }
+
+    /// Map the result with access to the state
+    fn st_map<B, F: Fn(Self::Out, &mut S) -> B>(self, f: F) -> StMap<Self, F> {
+        StMap { p: self, f }
+    }
+
+    /// Map the result with access to the state, failing if f returns an error.
+    /// Changes f makes to the state before failing are undone.
+    fn st_try_map<B, F>(self, f: F) -> StTryMap<Self, F>
+    where
+        F: Fn(Self::Out, &mut S) -> Result<B, Expected>,
+    {
+        StTryMap { p: self, f }
+    }
+}
+
+/// Runs p, putting the state back if it fails
+pub fn st_attempt<'a, S: Clone, P: StParser<'a, S>>(
+    p: &P,
+    it: &PIter<'a>,
+    st: &mut S,
+) -> ParseRes<'a, P::Out> {
+    let snap = st.clone();
+    let r = p.parse_st(it, st);
+    if r.is_err() {
+        *st = snap;
+    }
+    r
+}
+
+impl<'a, S, F, V> StParser<'a, S> for F
+where
+    F: Fn(&PIter<'a>, &mut S) -> ParseRes<'a, V>,
+{
+    type Out = V;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, V> {
+        self(it, st)
+    }
+}
+
+/// A Parser that ignores the state
+#[derive(Clone)]
+pub struct Lift<P>(pub P);
+
+impl<'a, S, P: Parser<'a>> StParser<'a, S> for Lift<P> {
+    type Out = P::Out;
+    fn parse_st(&self, it: &PIter<'a>, _: &mut S) -> ParseRes<'a, P::Out> {
+        self.0.parse(it)
+    }
+}
+
+pub fn lift<'a, P: Parser<'a>>(p: P) -> Lift<P> {
+    Lift(p)
+}
+
+impl<'a, S> StParser<'a, S> for char {
+    type Out = char;
+    fn parse_st(&self, it: &PIter<'a>, _: &mut S) -> ParseRes<'a, char> {
+        self.parse(it)
+    }
+}
+
+impl<'a, S> StParser<'a, S> for &'static str {
+    type Out = &'static str;
+    fn parse_st(&self, it: &PIter<'a>, _: &mut S) -> ParseRes<'a, &'static str> {
+        self.parse(it)
+    }
+}
+
+impl<'a, 'b, S, P: StParser<'a, S>> StParser<'a, S> for BRP<'b, P> {
+    type Out = P::Out;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, P::Out> {
+        self.0.parse_st(it, st)
+    }
+}
+
+pub struct StMap<P, F> {
+    p: P,
+    f: F,
+}
+
+impl<'a, S, P: StParser<'a, S>, B, F: Fn(P::Out, &mut S) -> B> StParser<'a, S> for StMap<P, F> {
+    type Out = B;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, B> {
+        let (ri, v, ex) = self.p.parse_st(it, st)?;
+        Ok((ri, (self.f)(v, st), ex))
+    }
+}
+
+pub fn st_map<'a, S, P, B, F>(p: P, f: F) -> StMap<P, F>
+where
+    P: StParser<'a, S>,
+    F: Fn(P::Out, &mut S) -> B,
+{
+    StMap { p, f }
+}
+
+pub struct StTryMap<P, F> {
+    p: P,
+    f: F,
+}
+
+impl<'a, S, P, B, F> StParser<'a, S> for StTryMap<P, F>
+where
+    S: Clone,
+    P: StParser<'a, S>,
+    F: Fn(P::Out, &mut S) -> Result<B, Expected>,
+{
+    type Out = B;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, B> {
+        let snap = st.clone();
+        let (ri, v, ex) = self.p.parse_st(it, st)?;
+        match (self.f)(v, st) {
+            Ok(v2) => Ok((ri, v2, ex)),
+            Err(e) => {
+                *st = snap;
+                ri.err_r(e)
+            }
+        }
+    }
+}
+
+pub fn st_try_map<'a, S, P, B, F>(p: P, f: F) -> StTryMap<P, F>
+where
+    S: Clone,
+    P: StParser<'a, S>,
+    F: Fn(P::Out, &mut S) -> Result<B, Expected>,
+{
+    StTryMap { p, f }
+}
+
+/// Read the state without consuming anything
+///
+/// ```rust
+/// use bogobble::*;
+/// let p = (lift(Alpha.plus()), get_st(|n: &usize| *n));
+/// assert_eq!(p.parse_st_s("abc", &mut 4), Ok(("abc", 4)));
+/// ```
+pub fn get_st<S, V, F: Fn(&S) -> V>(f: F) -> GetSt<F> {
+    GetSt { f }
+}
+
+pub struct GetSt<F> {
+    f: F,
+}
+
+impl<'a, S, V, F: Fn(&S) -> V> StParser<'a, S> for GetSt<F> {
+    type Out = V;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, V> {
+        Ok((*it, (self.f)(st), None))
+    }
+}
+
+impl<'a, S, A, B, V> StParser<'a, S> for Or<A, B>
+where
+    S: Clone,
+    A: StParser<'a, S, Out = V>,
+    B: StParser<'a, S, Out = V>,
+{
+    type Out = V;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, V> {
+        match st_attempt(&self.a, it, st) {
+            Ok(r) => Ok(r),
+            Err(e) if e.is_break => Err(e),
+            Err(e) => match st_attempt(&self.b, it, st) {
+                Ok(r) => Ok(r),
+                Err(e2) if e2.is_break => Err(e2),
+                Err(e2) => Err(e.longer(e2)),
+            },
+        }
+    }
+}
+
+pub fn st_or<'a, S, A, B, V>(a: A, b: B) -> Or<A, B>
+where
+    S: Clone,
+    A: StParser<'a, S, Out = V>,
+    B: StParser<'a, S, Out = V>,
+{
+    Or { a, b }
+}
+
+impl<'a, S: Clone, A: StParser<'a, S>> StParser<'a, S> for Maybe<A> {
+    type Out = Option<A::Out>;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, Self::Out> {
+        match st_attempt(&self.0, it, st) {
+            Ok((ir, v, ex)) => Ok((ir, Some(v), ex)),
+            Err(e) if e.is_cut => Err(e),
+            Err(e) => Ok((*it, None, Some(e))),
+        }
+    }
+}
+
+pub fn st_maybe<'a, S: Clone, A: StParser<'a, S>>(a: A) -> Maybe<A> {
+    Maybe(a)
+}
+
+fn do_st_rep<'a, S: Clone, A: StParser<'a, S>>(
+    i: &PIter<'a>,
+    st: &mut S,
+    a: &A,
+    min: usize,
+) -> ParseRes<'a, Vec<A::Out>> {
+    let snap = st.clone();
+    let mut it = *i;
+    let mut res = Vec::new();
+    loop {
+        match st_attempt(a, &it, st) {
+            Ok((i2, v, _)) => {
+                if it.index() == i2.index() {
+                    *st = snap;
+                    return Err(it.err_s("To Consume some data"));
+                }
+                res.push(v);
+                it = i2;
+            }
+            Err(e) => {
+                if res.len() >= min && !e.is_cut {
+                    return Ok((it, res, Some(e)));
+                }
+                *st = snap;
+                return Err(e);
+            }
+        }
+    }
+}
+
+fn do_st_sep<'a, S: Clone, A: StParser<'a, S>, B: StParser<'a, S>>(
+    i: &PIter<'a>,
+    st: &mut S,
+    a: &A,
+    b: &B,
+    min: usize,
+) -> ParseRes<'a, Vec<A::Out>> {
+    let snap = st.clone();
+    let mut res = Vec::new();
+    let mut ri = *i;
+    loop {
+        ri = match st_attempt(a, &ri, st) {
+            Ok((r, v, _)) => {
+                res.push(v);
+                r
+            }
+            Err(e) => {
+                if res.is_empty() && min == 0 && !e.is_cut {
+                    return Ok((ri, res, Some(e)));
+                }
+                *st = snap;
+                return Err(e);
+            }
+        };
+        ri = match st_attempt(b, &ri, st) {
+            Ok((r, _, _)) => r,
+            Err(e) => {
+                if res.len() < min || e.is_cut {
+                    *st = snap;
+                    return Err(e);
+                }
+                return Ok((ri, res, Some(e)));
+            }
+        };
+    }
+}
+
+impl<'a, S: Clone, A: StParser<'a, S>> StParser<'a, S> for Star<A> {
+    type Out = Vec<A::Out>;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, Self::Out> {
+        do_st_rep(it, st, &self.0, 0)
+    }
+}
+
+impl<'a, S: Clone, A: StParser<'a, S>> StParser<'a, S> for Plus<A> {
+    type Out = Vec<A::Out>;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, Self::Out> {
+        do_st_rep(it, st, &self.0, 1)
+    }
+}
+
+impl<'a, S: Clone, A: StParser<'a, S>, B: StParser<'a, S>> StParser<'a, S> for SepStar<A, B> {
+    type Out = Vec<A::Out>;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, Self::Out> {
+        do_st_sep(it, st, &self.a, &self.b, 0)
+    }
+}
+
+impl<'a, S: Clone, A: StParser<'a, S>, B: StParser<'a, S>> StParser<'a, S> for SepPlus<A, B> {
+    type Out = Vec<A::Out>;
+    fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, Self::Out> {
+        do_st_sep(it, st, &self.a, &self.b, 1)
+    }
+}
+
+pub fn st_star<'a, S: Clone, A: StParser<'a, S>>(a: A) -> Star<A> {
+    Star(a)
+}
+
+pub fn st_plus<'a, S: Clone, A: StParser<'a, S>>(a: A) -> Plus<A> {
+    Plus(a)
+}
+
+pub fn st_sep_star<'a, S: Clone, A: StParser<'a, S>, B: StParser<'a, S>>(
+    a: A,
+    b: B,
+) -> SepStar<A, B> {
+    SepStar { a, b }
+}
+
+pub fn st_sep_plus<'a, S: Clone, A: StParser<'a, S>, B: StParser<'a, S>>(
+    a: A,
+    b: B,
+) -> SepPlus<A, B> {
+    SepPlus { a, b }
+}
+
+macro_rules! st_tuple {
+    ($($t:ident $v:ident $n:tt),*) => {
+        impl<'a, S: Clone, $($t: StParser<'a, S>),*> StParser<'a, S> for ($($t,)*) {
+            type Out = ($($t::Out,)*);
+            fn parse_st(&self, it: &PIter<'a>, st: &mut S) -> ParseRes<'a, Self::Out> {
+                let snap = st.clone();
+                let ri = *it;
+                let ex = None;
+                $(
+                    let (ri, $v, ex) = match self.$n.parse_st(&ri, st).join_err_op(ex) {
+                        Ok(r) => r,
+                        Err(e) => {
+                            *st = snap;
+                            return Err(e);
+                        }
+                    };
+                )*
+                Ok((ri, ($($v,)*), ex))
+            }
+        }
+    };
+}
+
+st_tuple!(A a 0, B b 1);
+st_tuple!(A a 0, B b 1, C c 2);
+st_tuple!(A a 0, B b 1, C c 2, D d 3);
+st_tuple!(A a 0, B b 1, C c 2, D d 3, E e 4);
+st_tuple!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5);
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_heredoc_with_state() {
+        // <<TAG ... TAG, the state holds the tag to close on
+        let open = (lift("<<"), lift(Alpha.plus()), lift('\n'))
+            .st_map(|(_, t, _), st: &mut String| *st = t.to_string());
+        let line = || lift(Any.except("\n").star());
+        let body_line = st_try_map((line(), lift('\n')), |(l, _), st: &mut String| {
+            match l == st.as_str() {
+                true => Err(Expected::Str("not the closing tag")),
+                false => Ok(l),
+            }
+        });
+        let close = st_try_map(line(), |l, st: &mut String| match l == st.as_str() {
+            true => Ok(()),
+            false => Err(Expected::Str("closing tag")),
+        });
+        let p = (open, st_star(body_line), close);
+        let mut st = String::new();
+        let (_, b, _) = p
+            .parse_st_s("<<END\nhello\nEND there\nEND", &mut st)
+            .unwrap();
+        assert_eq!(b, vec!["hello", "END there"]);
+        assert_eq!(st, "END");
+    }
+
+    #[test]
+    fn test_sep_restores() {
+        let item = lift(NumDigit.plus()).st_map(|n, st: &mut Vec<String>| st.push(n.to_string()));
+        let sep = lift(',').st_map(|_, st: &mut Vec<String>| st.push(",".to_string()));
+        let p = st_sep_plus(item, sep);
+        let mut st = Vec::new();
+        // a trailing separator fails, as it does for sep_plus
+        let e = p.parse_st(&PIter::new("1,2,x"), &mut st).unwrap_err();
+        assert_eq!(
+            e.index,
+            sep_plus(NumDigit.plus(), ',')
+                .parse_s("1,2,x")
+                .unwrap_err()
+                .index
+        );
+        assert_eq!(e.index, Some(4));
+        assert!(st.is_empty());
+        let (it, v, _) = p.parse_st(&PIter::new("1,2 x"), &mut st).unwrap();
+        assert_eq!((v.len(), it.index()), (2, Some(3)));
+        assert_eq!(st, vec!["1", ",", "2"]);
+    }
+
+    #[test]
+    fn test_st_cut_breaks() {
+        let stmt = lift((ws_(keyword("let")), Cut, ws_(Alpha.plus())));
+        let mut st = 0usize;
+        let e = st_star::<usize, _>(stmt).parse_st(&PIter::new("let a let 3"), &mut st);
+        assert_eq!(e.unwrap_err().index, Some(10));
+        // a plain break is hidden, as it is by maybe
+        let r = st_maybe::<usize, _>(lift(('a', 'b').brk())).parse_st(&PIter::new("ac"), &mut st);
+        assert_eq!(r.unwrap().1, None);
+    }
+}
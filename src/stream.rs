@@ -0,0 +1,394 @@
+//! Parsing input from a [Read], without first loading it all into memory.
+//!
+//! The input is read in chunks into a window. Each item is parsed from the window, and if the
+//! parser reaches the end of the window before it is done, more data is read and the item is
+//! parsed again. Once an item is complete it can no longer be backtracked into,
+//! so the text it consumed is dropped from the window.
+//!
+//! ```rust
+//! use bogobble::*;
+//! let input = "a=1\nbb=22\nccc=333\n".as_bytes();
+//! let line = (string(Alpha.plus()), '=', common::Int, '\n').map(|(k, _, v, _)| (k, v));
+//! let res: Vec<_> = read_iter(input, line)
+//!     .chunk_size(4)
+//!     .collect::<Result<_, _>>()
+//!     .unwrap();
+//! assert_eq!(res[2], ("ccc".to_string(), 333));
+//! ```
+use crate::err::*;
+use crate::iter::*;
+use crate::parser::*;
+use std::cell::Cell;
+use std::fmt;
+use std::io::Read;
+use std::marker::PhantomData;
+
+#[derive(Debug)]
+pub enum StreamErr {
+    IO(std::io::Error),
+    Utf8(usize),
+    /// Boxed so every result of reading a stream is not as large as a StrungError
+    Parse(Box<StrungError>),
+}
+
+impl std::error::Error for StreamErr {}
+
+impl fmt::Display for StreamErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamErr::IO(e) => write!(f, "IO Error : {}", e),
+            StreamErr::Utf8(n) => write!(f, "Invalid UTF-8 at {}", n),
+            StreamErr::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for StreamErr {
+    fn from(e: std::io::Error) -> Self {
+        StreamErr::IO(e)
+    }
+}
+
+enum Attempt<V> {
+    More,
+    End,
+    Item(usize, V),
+    Fail(StrungError),
+}
+
+/// A buffered window over a [Read] that parsers can pull items from.
+pub struct StreamReader<R> {
+    r: R,
+    buf: String,
+    /// bytes read that do not yet make a whole char
+    rest: Vec<u8>,
+    eof: bool,
+    chunk: usize,
+    /// bytes at the front of buf, already parsed and waiting to be dropped
+    consumed: usize,
+    /// position of the start of buf in the whole input
+    offset: usize,
+    line: usize,
+    col: usize,
+    conf: LcConf,
+    /// Set when the last attempt read up to the end of the window
+    edge: Cell<bool>,
+}
+
+impl<R: Read> StreamReader<R> {
+    pub fn new(r: R) -> Self {
+        StreamReader {
+            r,
+            buf: String::new(),
+            rest: Vec::new(),
+            eof: false,
+            chunk: 4096,
+            consumed: 0,
+            offset: 0,
+            line: 0,
+            col: 0,
+            conf: LcConf::default(),
+            edge: Cell::new(false),
+        }
+    }
+
+    /// Set how columns are counted in errors
+    pub fn col_mode(mut self, cols: ColMode) -> Self {
+        self.conf.cols = cols;
+        self
+    }
+
+    /// Set how lines and columns are counted in errors
+    pub fn lc_conf(mut self, conf: LcConf) -> Self {
+        self.conf = conf;
+        self
+    }
+
+    /// Set the minimum number of bytes requested per read
+    pub fn chunk_size(mut self, n: usize) -> Self {
+        self.chunk = n.max(1);
+        self
+    }
+
+    /// The current window, Borrowed results can only ever refer to this
+    pub fn window(&self) -> &str {
+        &self.buf[self.consumed..]
+    }
+
+    /// The offset in the whole input of the start of the window
+    pub fn offset(&self) -> usize {
+        self.offset + self.consumed
+    }
+
+    fn drop_consumed(&mut self) {
+        let mut it = self.iter();
+        while it.index().map(|i| i < self.consumed).unwrap_or(false) {
+            it.next();
+        }
+        let (line, col) = it.lc();
+        self.line = line;
+        self.col = col;
+        self.buf.drain(..self.consumed);
+        self.offset += self.consumed;
+        self.consumed = 0;
+    }
+
+    /// Reads at least a chunk more, or as much as there is already in the window,
+    /// so that reparsing long items stays linear.
+    fn fill(&mut self) -> Result<(), StreamErr> {
+        let want = self.chunk.max(self.buf.len());
+        let start = self.rest.len();
+        self.rest.resize(start + want, 0);
+        let n = loop {
+            match self.r.read(&mut self.rest[start..]) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.rest.truncate(start);
+                    return Err(e.into());
+                }
+            }
+        };
+        self.rest.truncate(start + n);
+        if n == 0 {
+            self.eof = true;
+        }
+        let valid = match std::str::from_utf8(&self.rest) {
+            Ok(s) => s.len(),
+            Err(e) => match e.error_len() {
+                Some(_) => {
+                    let at = self.offset + self.buf.len() + e.valid_up_to();
+                    return Err(StreamErr::Utf8(at));
+                }
+                None => e.valid_up_to(),
+            },
+        };
+        if self.eof && valid < self.rest.len() {
+            return Err(StreamErr::Utf8(self.offset + self.buf.len() + valid));
+        }
+        let s = std::str::from_utf8(&self.rest[..valid]).unwrap_or("");
+        self.buf.push_str(s);
+        self.rest.drain(..valid);
+        Ok(())
+    }
+
+    fn shift_err(&self, mut e: StrungError) -> StrungError {
+        e.index = e.index.map(|i| i + self.offset);
+        e.end = e.end.map(|i| i + self.offset);
+        e.child = e.child.map(|c| Box::new(self.shift_err(*c)));
+        e
+    }
+
+    fn iter(&self) -> PIter<'_> {
+        PIter::with_conf(&self.buf, self.conf)
+            .with_lc((self.line, self.col))
+            .watch_edge(&self.edge)
+    }
+
+    fn attempt<'x, P: Parser<'x>>(&'x self, p: &P) -> Attempt<P::Out> {
+        if self.eof && self.buf.is_empty() {
+            return Attempt::End;
+        }
+        let it = self.iter();
+        self.edge.set(false);
+        let r = p.parse(&it);
+        // Anything that looked at the end of the window, even if it backtracked or failed
+        // elsewhere, might parse differently with more input
+        if self.edge.get() && !self.eof {
+            return Attempt::More;
+        }
+        match r {
+            Ok((nit, v, _)) => match nit.index().unwrap_or(self.buf.len()) {
+                0 => Attempt::Fail(self.shift_err(it.err_s("To Consume some data").strung())),
+                n => Attempt::Item(n, v),
+            },
+            Err(e) if e.index.is_none() && !self.eof => Attempt::More,
+            Err(e) => Attempt::Fail(self.shift_err(e.strung())),
+        }
+    }
+
+    /// Reads until the parser has a complete result, returning how many bytes it needed
+    fn ready<P: for<'x> Parser<'x>>(&mut self, p: &P) -> Option<Result<usize, StreamErr>> {
+        self.drop_consumed();
+        loop {
+            let done = match self.attempt(p) {
+                Attempt::More => None,
+                Attempt::End => Some(None),
+                Attempt::Item(n, _) => Some(Some(Ok(n))),
+                Attempt::Fail(e) => Some(Some(Err(StreamErr::Parse(Box::new(e))))),
+            };
+            match done {
+                Some(r) => return r,
+                None => {
+                    if let Err(e) = self.fill() {
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parse the next item with an owned result.
+    pub fn next_item<P: OParser<O>, O>(&mut self, p: &P) -> Option<Result<O, StreamErr>> {
+        self.drop_consumed();
+        loop {
+            match self.attempt(p) {
+                Attempt::More => {
+                    if let Err(e) = self.fill() {
+                        return Some(Err(e));
+                    }
+                }
+                Attempt::End => return None,
+                Attempt::Item(n, v) => {
+                    self.consumed = n;
+                    return Some(Ok(v));
+                }
+                Attempt::Fail(e) => return Some(Err(StreamErr::Parse(Box::new(e)))),
+            }
+        }
+    }
+
+    /// Parse the next item, allowing results that borrow from the current window.
+    /// The final attempt at the item is parsed twice, so prefer [StreamReader::next_item]
+    /// when the result is owned.
+    ///
+    /// ```rust
+    /// use bogobble::*;
+    /// let mut sr = StreamReader::new("one two  three".as_bytes()).chunk_size(2);
+    /// let word = ws_(Alpha.plus());
+    /// assert_eq!(sr.parse_next(&word).unwrap().unwrap(), "one");
+    /// assert_eq!(sr.parse_next(&word).unwrap().unwrap(), "two");
+    /// assert_eq!(sr.parse_next(&word).unwrap().unwrap(), "three");
+    /// assert!(sr.parse_next(&word).is_none());
+    /// ```
+    pub fn parse_next<'s, P>(
+        &'s mut self,
+        p: &P,
+    ) -> Option<Result<<P as Parser<'s>>::Out, StreamErr>>
+    where
+        P: for<'x> Parser<'x>,
+    {
+        let n = match self.ready(p)? {
+            Ok(n) => n,
+            Err(e) => return Some(Err(e)),
+        };
+        self.consumed = n;
+        let (_, v, _) = p.parse(&self.iter()).ok()?;
+        Some(Ok(v))
+    }
+}
+
+/// An iterator of owned results from a [Read], see [read_iter]
+pub struct StreamIter<R, P, O> {
+    sr: StreamReader<R>,
+    p: P,
+    failed: bool,
+    phantom: PhantomData<fn() -> O>,
+}
+
+impl<R: Read, P, O> StreamIter<R, P, O> {
+    pub fn chunk_size(mut self, n: usize) -> Self {
+        self.sr = self.sr.chunk_size(n);
+        self
+    }
+}
+
+impl<R: Read, P: OParser<O>, O> Iterator for StreamIter<R, P, O> {
+    type Item = Result<O, StreamErr>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let r = self.sr.next_item(&self.p);
+        if let Some(Err(_)) = r {
+            self.failed = true;
+        }
+        r
+    }
+}
+
+/// Parse items one after the other from a reader, stopping at the end of the input or the
+/// first error.
+pub fn read_iter<R: Read, P: OParser<O>, O>(r: R, p: P) -> StreamIter<R, P, O> {
+    StreamIter {
+        sr: StreamReader::new(r),
+        p,
+        failed: false,
+        phantom: PhantomData,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_stream_errors_are_absolute() {
+        let input = "12,34,\n56,x7,".as_bytes();
+        let r: Vec<_> = read_iter(input, wn_(first(common::Int, ',')))
+            .chunk_size(3)
+            .collect();
+        assert_eq!(r.len(), 4);
+        match &r[3] {
+            Err(StreamErr::Parse(e)) => {
+                assert_eq!(e.index, Some(10));
+                assert_eq!((e.line, e.col), (1, 3));
+            }
+            _ => panic!("expected parse error"),
+        }
+    }
+
+    #[test]
+    fn test_stream_splits_chars() {
+        let input = "héllo wörld".as_bytes();
+        let r: Vec<String> = read_iter(input, ws_(string(Any.except(" ").plus())))
+            .chunk_size(1)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(r, vec!["héllo", "wörld"]);
+    }
+
+    #[test]
+    fn test_stream_splits_literals() {
+        for n in [1, 2, 3, 64] {
+            let input = "abcd;abcd;".as_bytes();
+            let r: Vec<_> = read_iter(input, first("abcd", ';'))
+                .chunk_size(n)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(r, vec!["abcd", "abcd"]);
+            let p = first(matching("abcdef"), ';').ig();
+            let r: Vec<_> = read_iter("abcdef;abcdef;".as_bytes(), p)
+                .chunk_size(n)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(r.len(), 2);
+            let input = "let a;let bc;".as_bytes();
+            let p = (keyword("let"), ws_(string(Alpha.plus())), ';').map(|(_, v, _)| v);
+            let r: Vec<_> = read_iter(input, p)
+                .chunk_size(n)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(r, vec!["a", "bc"]);
+        }
+    }
+
+    #[test]
+    fn test_stream_utf8_offset() {
+        let input = &b"ab;\xffc;"[..];
+        let mut r = read_iter(input, first(string(Alpha.plus()), ';'));
+        match r.next() {
+            Some(Err(StreamErr::Utf8(n))) => assert_eq!(n, 3),
+            _ => panic!("expected utf8 error"),
+        }
+        let input = &b"ab;c\xe6"[..];
+        let r: Vec<_> = read_iter(input, first(string(Alpha.plus()), ';'))
+            .chunk_size(2)
+            .collect();
+        match r.last() {
+            Some(Err(StreamErr::Utf8(n))) => assert_eq!(*n, 4),
+            _ => panic!("expected utf8 error"),
+        }
+    }
+}
@@ -44,7 +44,7 @@ pub fn do_strings_until<'a, A: Parser<'a, Out = C>, B: Parser<'a>, C: AsRef<str>
     min: usize,
 ) -> ParseRes<'a, (String, B::Out)> {
     let mut res = String::new();
-    let mut it = it.clone();
+    let mut it = *it;
     let mut done = 0;
     loop {
         let b_err = if done >= min {
@@ -85,7 +85,7 @@ where
     type Out = String;
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, String> {
         let mut res = String::new();
-        let mut it = it.clone();
+        let mut it = *it;
         loop {
             match self.a.parse(&it) {
                 Ok((i, v, _)) => {
@@ -93,7 +93,7 @@ where
                     it = i;
                 }
                 Err(e) => {
-                    if res.len() > 0 {
+                    if !res.is_empty() {
                         return Ok((it, res, Some(e)));
                     }
                     return Err(e);
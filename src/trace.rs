@@ -0,0 +1,331 @@
+//! Recording a tree of the rules tried during a parse.
+//!
+//! While [traced] runs, every rule made by [parser!] and every [crate::debug] adds a
+//! [TraceNode] with where it started, and where it finished or the error it gave. At
+//! [TraceLevel::All], each [crate::or] is recorded too, so failed alternatives can be seen.
+//! The tree can be written as indented text, JSON or Graphviz dot.
+//!
+//! This needs the `trace` feature, without it the hooks in rules are compiled away.
+//!
+//! ```rust
+//! use bogobble::*;
+//! parser!(Key, "key");
+//! parser!(Val, "val");
+//! parser!((Item->&'static str) ws_(or(Key, Val)));
+//!
+//! let (r, tree) = traced(TraceLevel::Rules, || (Item, Item).parse_s("val key"));
+//! assert!(r.is_ok());
+//! assert_eq!(
+//!     tree.to_text(),
+//!     "Item 0..3
+//!   Key 0 failed at 0: expected Key
+//!   Val 0..3
+//! Item 3..7
+//!   Key 4..7
+//! "
+//! );
+//! assert!(tree.to_json().starts_with(r#"[{"name":"Item","start":0,"end":3,"children":["#));
+//! assert!(tree.to_dot().contains("n0 -> n1;"));
+//! ```
+use crate::err::*;
+use crate::hooks::{hooks, set_hooks, LEVEL};
+use crate::iter::*;
+use crate::parser::*;
+use std::cell::RefCell;
+use std::fmt::Write;
+
+/// What gets recorded by [traced]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, PartialOrd, Ord)]
+pub enum TraceLevel {
+    /// Rules from [parser!], and [crate::debug] points
+    #[default]
+    Rules,
+    /// Rules, and combinators that choose between alternatives
+    All,
+}
+
+/// One parser run, with the parsers it called as children
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceNode {
+    pub name: &'static str,
+    pub start: usize,
+    /// The offset it finished at, or the error it failed with
+    pub result: Result<usize, StrungError>,
+    pub children: Vec<TraceNode>,
+}
+
+/// The top level [TraceNode]s recorded by [traced], in the order they ran
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TraceTree {
+    pub roots: Vec<TraceNode>,
+}
+
+thread_local! {
+    /// The nodes still running, the first is a holder for the roots
+    static STACK: RefCell<Vec<TraceNode>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Puts back the outer trace when dropped, even if the traced function panics
+struct Restore {
+    level: u8,
+    stack: Vec<TraceNode>,
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        set_hooks(LEVEL, self.level);
+        let stack = std::mem::take(&mut self.stack);
+        STACK.with(|s| s.replace(stack));
+    }
+}
+
+fn holder() -> TraceNode {
+    TraceNode {
+        name: "",
+        start: 0,
+        result: Ok(0),
+        children: Vec::new(),
+    }
+}
+
+/// Runs f, recording what is parsed on this thread. Traces can be nested, the inner one does
+/// not appear in the outer.
+pub fn traced<R, F: FnOnce() -> R>(level: TraceLevel, f: F) -> (R, TraceTree) {
+    let restore = Restore {
+        level: set_hooks(LEVEL, level as u8 + 1),
+        stack: STACK.with(|s| s.replace(vec![holder()])),
+    };
+    let r = f();
+    let stack = STACK.with(|s| s.take());
+    drop(restore);
+    let roots = stack
+        .into_iter()
+        .next()
+        .map(|h| h.children)
+        .unwrap_or_default();
+    (r, TraceTree { roots })
+}
+
+pub fn is_tracing() -> bool {
+    hooks() & LEVEL > 0
+}
+
+pub(crate) fn record<'a, V, F: FnOnce() -> ParseRes<'a, V>>(
+    name: &'static str,
+    it: &PIter<'a>,
+    f: F,
+) -> ParseRes<'a, V> {
+    STACK.with(|s| {
+        s.borrow_mut().push(TraceNode {
+            name,
+            start: it.offset(),
+            result: Ok(it.offset()),
+            children: Vec::new(),
+        })
+    });
+    let r = f();
+    STACK.with(|s| {
+        let mut s = s.borrow_mut();
+        if s.len() < 2 {
+            return;
+        }
+        if let Some(mut node) = s.pop() {
+            node.result = match &r {
+                Ok((r, _, _)) => Ok(r.offset()),
+                Err(e) => Err(e.clone().strung()),
+            };
+            if let Some(parent) = s.last_mut() {
+                parent.children.push(node);
+            }
+        }
+    });
+    r
+}
+
+fn esc_json(s: &str) -> String {
+    let mut res = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(res, "\\u{:04x}", c as u32);
+            }
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+fn index_str(i: Option<usize>) -> String {
+    match i {
+        Some(n) => n.to_string(),
+        None => "EOI".to_string(),
+    }
+}
+
+impl TraceNode {
+    /// A one line summary, without the children
+    pub fn label(&self) -> String {
+        match &self.result {
+            Ok(end) => format!("{} {}..{}", self.name, self.start, end),
+            Err(e) => format!(
+                "{} {} failed at {}: expected {}",
+                self.name,
+                self.start,
+                index_str(e.index),
+                e.exp
+            ),
+        }
+    }
+
+    fn write_text(&self, depth: usize, s: &mut String) {
+        let _ = writeln!(s, "{:w$}{}", "", self.label(), w = depth * 2);
+        for c in &self.children {
+            c.write_text(depth + 1, s);
+        }
+    }
+
+    fn write_json(&self, s: &mut String) {
+        let _ = write!(
+            s,
+            r#"{{"name":"{}","start":{},"#,
+            esc_json(self.name),
+            self.start
+        );
+        match &self.result {
+            Ok(end) => {
+                let _ = write!(s, r#""end":{},"#, end);
+            }
+            Err(e) => {
+                let idx = e.index.map(|n| n.to_string());
+                let _ = write!(
+                    s,
+                    r#""error":{{"index":{},"expected":"{}"}},"#,
+                    idx.as_deref().unwrap_or("null"),
+                    esc_json(&e.exp.to_string())
+                );
+            }
+        }
+        s.push_str(r#""children":["#);
+        for (n, c) in self.children.iter().enumerate() {
+            if n > 0 {
+                s.push(',');
+            }
+            c.write_json(s);
+        }
+        s.push_str("]}");
+    }
+
+    /// Writes this node as n{id}, then its children, returning the next free id
+    fn write_dot(&self, id: usize, s: &mut String) -> usize {
+        let color = match self.result {
+            Ok(_) => "black",
+            Err(_) => "red",
+        };
+        let _ = writeln!(
+            s,
+            "  n{} [label=\"{}\", color={}];",
+            id,
+            esc_json(&self.label()),
+            color
+        );
+        let mut next = id + 1;
+        for c in &self.children {
+            let _ = writeln!(s, "  n{} -> n{};", id, next);
+            next = c.write_dot(next, s);
+        }
+        next
+    }
+}
+
+impl TraceTree {
+    /// One line per node, children indented below their parent
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        for r in &self.roots {
+            r.write_text(0, &mut s);
+        }
+        s
+    }
+
+    /// An array of the roots, each node is
+    /// `{"name", "start", "end" or "error":{"index","expected"}, "children"}`
+    pub fn to_json(&self) -> String {
+        let mut s = String::from("[");
+        for (n, r) in self.roots.iter().enumerate() {
+            if n > 0 {
+                s.push(',');
+            }
+            r.write_json(&mut s);
+        }
+        s.push(']');
+        s
+    }
+
+    /// A Graphviz digraph, failed nodes are red
+    pub fn to_dot(&self) -> String {
+        let mut s = String::from("digraph trace {\n");
+        let mut id = 0;
+        for r in &self.roots {
+            id = r.write_dot(id, &mut s);
+        }
+        s.push_str("}\n");
+        s
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_trace_levels() {
+        let p = or(debug('a', "A"), debug('b', "B"));
+        let (_, t) = traced(TraceLevel::Rules, || p.parse_s("b"));
+        assert_eq!(t.roots.len(), 2);
+        let (_, t) = traced(TraceLevel::All, || p.parse_s("b"));
+        assert_eq!(t.roots.len(), 1);
+        assert_eq!(
+            t.roots[0].children[0].label(),
+            "A 0 failed at 0: expected a"
+        );
+        assert_eq!(
+            t.to_json(),
+            r#"[{"name":"or","start":0,"end":1,"children":[{"name":"A","start":0,"error":{"index":0,"expected":"a"},"children":[]},{"name":"B","start":0,"end":1,"children":[]}]}]"#
+        );
+        assert_eq!(
+            t.to_dot(),
+            "digraph trace {
+  n0 [label=\"or 0..1\", color=black];
+  n0 -> n1;
+  n1 [label=\"A 0 failed at 0: expected a\", color=red];
+  n0 -> n2;
+  n2 [label=\"B 0..1\", color=black];
+}
+"
+        );
+        // nothing is kept once the trace is done
+        assert!(!is_tracing());
+        assert_eq!(traced(TraceLevel::All, || ()).1, TraceTree::default());
+    }
+
+    #[test]
+    fn test_trace_panic_restores() {
+        let p = debug('a', "A");
+        let (_, t) = traced(TraceLevel::Rules, || {
+            let r = std::panic::catch_unwind(|| {
+                traced(TraceLevel::All, || {
+                    p.br().map(|_| panic!("inside")).parse_s("a")
+                })
+            });
+            assert!(r.is_err());
+            assert!(is_tracing());
+            p.parse_s("a")
+        });
+        assert_eq!(t.to_text(), "A 0..1\n");
+        assert!(!is_tracing());
+    }
+}
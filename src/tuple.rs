@@ -9,9 +9,15 @@ where
     type Out = (A::Out, B::Out);
     fn parse(&self, it: &PIter<'a>) -> ParseRes<'a, Self::Out> {
         let (it2, av, c1) = self.0.parse(it)?;
-        let (it3, bv, c2) = self.1.parse(&it2).join_err_op(c1)?;
+        let (it3, bv, c2) = match self.1.parse(&it2).join_err_op(c1) {
+            Err(e) if self.0.is_cut() => return Err(e.cut()),
+            r => r?,
+        };
         Ok((it3, (av, bv), c2))
     }
+    fn is_cut(&self) -> bool {
+        self.0.is_cut() || self.1.is_cut()
+    }
 }
 
 impl<'a, A, B, C> Parser<'a> for (A, B, C)
@@ -26,6 +32,9 @@ where
             .parse(it)
             .map_v(|((a, b), c)| (a, b, c))
     }
+    fn is_cut(&self) -> bool {
+        self.0.is_cut() || self.1.is_cut() || self.2.is_cut()
+    }
 }
 
 impl<'a, A, B, C, D> Parser<'a> for (A, B, C, D)
@@ -41,6 +50,9 @@ where
             .parse(it)
             .map_v(|((a, b), (c, d))| (a, b, c, d))
     }
+    fn is_cut(&self) -> bool {
+        self.0.is_cut() || self.1.is_cut() || self.2.is_cut() || self.3.is_cut()
+    }
 }
 impl<'a, A, B, C, D, E> Parser<'a> for (A, B, C, D, E)
 where
@@ -59,6 +71,9 @@ where
             .parse(it)
             .map_v(|((a, b, c), (d, e))| (a, b, c, d, e))
     }
+    fn is_cut(&self) -> bool {
+        self.0.is_cut() || self.1.is_cut() || self.2.is_cut() || self.3.is_cut() || self.4.is_cut()
+    }
 }
 impl<'a, A, B, C, D, E, F> Parser<'a> for (A, B, C, D, E, F)
 where
@@ -78,6 +93,14 @@ where
             .parse(it)
             .map_v(|((a, b, c), (d, e, f))| (a, b, c, d, e, f))
     }
+    fn is_cut(&self) -> bool {
+        self.0.is_cut()
+            || self.1.is_cut()
+            || self.2.is_cut()
+            || self.3.is_cut()
+            || self.4.is_cut()
+            || self.5.is_cut()
+    }
 }
 
 //Grabbing specific elements